@@ -0,0 +1,122 @@
+//! Compares brute-force collision checks against the `SpatialHash`
+//! broad-phase in a scene of 10k bouncing obstacles and 5k platforms.
+//!
+//! Run with `cargo bench --bench broad_phase`.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use criterion::{criterion_group, criterion_main, Criterion};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use template_bevy::components::{Bounce, BoxCollider, Obstacle, Platform};
+use template_bevy::resources::SpatialHash;
+use template_bevy::systems::{bounce_obstacles, update_spatial_hash};
+
+const OBSTACLES: usize = 10_000;
+const PLATFORMS: usize = 5_000;
+/// Size of the square the scene is scattered over
+const WORLD_SIZE: f32 = 20_000.0;
+
+fn spawn_scene(world: &mut World) {
+    let mut rng = StdRng::seed_from_u64(39);
+    let mut position = || {
+        Vec3::new(
+            rng.gen_range(0.0..WORLD_SIZE),
+            rng.gen_range(0.0..WORLD_SIZE),
+            0.0,
+        )
+    };
+
+    let platforms: Vec<_> = (0..PLATFORMS)
+        .map(|_| {
+            (
+                Platform,
+                BoxCollider::new(160.0, 20.0),
+                Transform::from_translation(position()),
+            )
+        })
+        .collect();
+    world.spawn_batch(platforms);
+
+    let obstacles: Vec<_> = (0..OBSTACLES)
+        .map(|_| {
+            (
+                Obstacle,
+                Bounce::new(980.0, 0.8),
+                BoxCollider::new(30.0, 30.0),
+                Transform::from_translation(position()),
+            )
+        })
+        .collect();
+    world.spawn_batch(obstacles);
+}
+
+/// The bounce check as it was before the broad-phase: every obstacle is
+/// tested against every platform
+#[allow(clippy::type_complexity)]
+fn bounce_obstacles_brute_force(
+    time: Res<Time>,
+    mut query: Query<(&mut Bounce, &mut Transform, &BoxCollider)>,
+    platform_query: Query<(&Transform, &BoxCollider), (With<Platform>, Without<Bounce>)>,
+) {
+    let delta = time.delta_seconds();
+    for (mut bounce, mut transform, collider) in query.iter_mut() {
+        bounce.velocity_y -= bounce.gravity * delta;
+        let previous_bottom = transform.translation.y - collider.height / 2.0;
+        transform.translation.y += bounce.velocity_y * delta;
+        if bounce.velocity_y > 0.0 {
+            continue;
+        }
+
+        let bottom = transform.translation.y - collider.height / 2.0;
+        for (platform_transform, platform_collider) in platform_query.iter() {
+            let platform_top = platform_transform.translation.y + platform_collider.height / 2.0;
+            let horizontal_overlap = (transform.translation.x - platform_transform.translation.x)
+                .abs()
+                < (collider.width + platform_collider.width) / 2.0;
+            if horizontal_overlap && previous_bottom >= platform_top && bottom <= platform_top {
+                transform.translation.y = platform_top + collider.height / 2.0;
+                bounce.velocity_y = -bounce.velocity_y * bounce.restitution;
+                break;
+            }
+        }
+    }
+}
+
+fn scene_app() -> App {
+    let mut app = App::new();
+    app.init_resource::<Time>();
+    spawn_scene(app.world_mut());
+    app
+}
+
+fn step(app: &mut App) {
+    app.world_mut()
+        .resource_mut::<Time>()
+        .advance_by(Duration::from_secs_f32(1.0 / 60.0));
+    app.update();
+}
+
+fn bench_broad_phase(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bounce_10k_obstacles_5k_platforms");
+    group.sample_size(10);
+
+    group.bench_function("brute_force", |b| {
+        let mut app = scene_app();
+        app.add_systems(Update, bounce_obstacles_brute_force);
+        b.iter(|| step(&mut app));
+    });
+
+    group.bench_function("spatial_hash", |b| {
+        let mut app = scene_app();
+        app.init_resource::<SpatialHash>()
+            .add_systems(Update, (update_spatial_hash, bounce_obstacles).chain());
+        b.iter(|| step(&mut app));
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_broad_phase);
+criterion_main!(benches);
@@ -0,0 +1,85 @@
+//! Compares spawning and despawning obstacles every frame against recycling
+//! them through an `EntityPool`.
+//!
+//! Run with `cargo bench --bench obstacle_pool`.
+
+use bevy::prelude::*;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use template_bevy::components::{AutoMove, BoxCollider, DamageOnContact, Obstacle};
+use template_bevy::resources::EntityPool;
+
+/// Obstacles replaced every frame
+const BATCH_SIZES: [usize; 3] = [10, 100, 1000];
+
+#[derive(Resource)]
+struct BatchSize(usize);
+
+fn obstacle_bundle(i: usize) -> impl Bundle {
+    (
+        Obstacle,
+        AutoMove::left(150.0),
+        BoxCollider::new(40.0, 40.0),
+        DamageOnContact::new(10.0),
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::srgb(0.8, 0.2, 0.2),
+                custom_size: Some(Vec2::splat(40.0)),
+                ..default()
+            },
+            transform: Transform::from_xyz(i as f32, 0.0, 0.0),
+            ..default()
+        },
+    )
+}
+
+/// Despawns every obstacle and spawns a fresh batch
+fn churn(mut commands: Commands, batch: Res<BatchSize>, obstacles: Query<Entity, With<Obstacle>>) {
+    for entity in obstacles.iter() {
+        commands.entity(entity).despawn();
+    }
+    for i in 0..batch.0 {
+        commands.spawn(obstacle_bundle(i));
+    }
+}
+
+/// Releases every obstacle to the pool and acquires a fresh batch
+fn recycle(
+    mut commands: Commands,
+    batch: Res<BatchSize>,
+    mut pool: ResMut<EntityPool<Obstacle>>,
+    obstacles: Query<Entity, With<Obstacle>>,
+) {
+    for entity in obstacles.iter() {
+        pool.release(&mut commands, entity);
+    }
+    for i in 0..batch.0 {
+        pool.acquire(&mut commands).insert(obstacle_bundle(i));
+    }
+}
+
+fn bench_obstacle_churn(c: &mut Criterion) {
+    let mut group = c.benchmark_group("obstacle_churn");
+    for batch in BATCH_SIZES {
+        group.bench_with_input(
+            BenchmarkId::new("spawn_despawn", batch),
+            &batch,
+            |b, &batch| {
+                let mut app = App::new();
+                app.insert_resource(BatchSize(batch))
+                    .add_systems(Update, churn);
+                b.iter(|| app.update());
+            },
+        );
+        group.bench_with_input(BenchmarkId::new("pooled", batch), &batch, |b, &batch| {
+            let mut app = App::new();
+            app.insert_resource(BatchSize(batch))
+                .insert_resource(EntityPool::<Obstacle>::new(batch))
+                .add_systems(Update, recycle);
+            b.iter(|| app.update());
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_obstacle_churn);
+criterion_main!(benches);
@@ -174,15 +174,15 @@ fn check_collisions(
 
             let h_overlap = p_left < plat_right && p_right > plat_left;
 
-            if h_overlap && velocity.0.y <= 0.0 {
-                if p_bottom <= plat_top
-                    && p_bottom >= plat_top - LANDING_THRESHOLD
-                    && p_top > plat_top
-                {
-                    player_tf.translation.y = plat_top + ph_h;
-                    velocity.0.y = 0.0;
-                    is_grounded = true;
-                }
+            if h_overlap
+                && velocity.0.y <= 0.0
+                && p_bottom <= plat_top
+                && p_bottom >= plat_top - LANDING_THRESHOLD
+                && p_top > plat_top
+            {
+                player_tf.translation.y = plat_top + ph_h;
+                velocity.0.y = 0.0;
+                is_grounded = true;
             }
         }
         grounded.0 = is_grounded;
@@ -190,7 +190,7 @@ fn check_collisions(
 }
 
 fn print_score(score: Res<Score>, mut last_score: Local<u32>) {
-    if score.current != *last_score && score.current % 100 == 0 {
+    if score.current != *last_score && score.current.is_multiple_of(100) {
         println!(
             "Score: {} (High Score: {})",
             score.current, score.high_score
@@ -6,7 +6,8 @@
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
 
-use crate::game::constants::camera::{MAX_SMOOTHING, MIN_SMOOTHING};
+use crate::game::narrow_phase::{self, Contact};
+use crate::level::LevelData;
 
 /// Marker component for the player entity
 #[derive(Component, Debug, Default)]
@@ -64,18 +65,120 @@ impl Health {
 #[derive(Component, Debug, Default, Clone)]
 pub struct Velocity(pub Vec2);
 
-/// Marker component for entities that can collide
-#[derive(Component, Debug, Default)]
-pub struct Collider {
-    pub radius: f32,
+/// Collision shape used for precise contacts. The entity's [`BoxCollider`]
+/// must enclose it, as the broad phase only looks at the box; entities
+/// without a shape collide as their box.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub enum Collider {
+    /// Axis-aligned box
+    Aabb {
+        half_size: Vec2,
+    },
+    Circle {
+        radius: f32,
+    },
+    /// Vertical capsule: a segment reaching `half_height` above and below the
+    /// center, swept by `radius`
+    Capsule {
+        radius: f32,
+        half_height: f32,
+    },
+    /// Box rotated counterclockwise by `angle` radians
+    Obb {
+        half_size: Vec2,
+        angle: f32,
+    },
 }
 
 impl Collider {
-    pub fn new(radius: f32) -> Self {
-        Self { radius }
+    pub fn aabb(width: f32, height: f32) -> Self {
+        Self::Aabb {
+            half_size: Vec2::new(width, height) / 2.0,
+        }
+    }
+
+    pub fn circle(radius: f32) -> Self {
+        Self::Circle { radius }
+    }
+
+    pub fn capsule(radius: f32, half_height: f32) -> Self {
+        Self::Capsule {
+            radius,
+            half_height,
+        }
+    }
+
+    pub fn obb(width: f32, height: f32, angle: f32) -> Self {
+        Self::Obb {
+            half_size: Vec2::new(width, height) / 2.0,
+            angle,
+        }
+    }
+
+    /// The largest shape of `kind` that fits in a box of `size`
+    pub fn fitted(kind: ShapeKind, size: Vec2) -> Self {
+        match kind {
+            ShapeKind::Box => Self::aabb(size.x, size.y),
+            ShapeKind::Circle => Self::circle(size.min_element() / 2.0),
+            ShapeKind::Capsule => {
+                let radius = size.min_element() / 2.0;
+                Self::capsule(radius, size.y / 2.0 - radius)
+            }
+        }
+    }
+
+    /// Size of the smallest axis-aligned box around the shape
+    pub fn bounding_size(&self) -> Vec2 {
+        match *self {
+            Self::Aabb { half_size } => half_size * 2.0,
+            Self::Circle { radius } => Vec2::splat(radius * 2.0),
+            Self::Capsule {
+                radius,
+                half_height,
+            } => Vec2::new(radius, radius + half_height) * 2.0,
+            Self::Obb { half_size, angle } => {
+                let (sin, cos) = angle.sin_cos();
+                let x = half_size.x * cos.abs() + half_size.y * sin.abs();
+                let y = half_size.x * sin.abs() + half_size.y * cos.abs();
+                Vec2::new(x, y) * 2.0
+            }
+        }
+    }
+
+    /// Contact with `other`, or `None` when they do not overlap. The normal
+    /// points from `other` toward this shape.
+    pub fn contact(
+        &self,
+        position: Vec2,
+        other: &Collider,
+        other_position: Vec2,
+    ) -> Option<Contact> {
+        narrow_phase::contact(self, position, other, other_position)
+    }
+}
+
+impl Default for Collider {
+    fn default() -> Self {
+        Self::aabb(50.0, 50.0)
     }
 }
 
+impl From<&BoxCollider> for Collider {
+    fn from(collider: &BoxCollider) -> Self {
+        Self::aabb(collider.width, collider.height)
+    }
+}
+
+/// Shape fitted to a collider's box when spawned from config or level data
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ShapeKind {
+    #[default]
+    Box,
+    Circle,
+    /// As wide as the box, with round ends
+    Capsule,
+}
+
 /// Gravity component for entities affected by gravity
 #[derive(Component, Debug, Clone)]
 pub struct Gravity(pub f32);
@@ -90,10 +193,110 @@ impl Default for Gravity {
 #[derive(Component, Debug, Default, Clone)]
 pub struct Grounded(pub bool);
 
-/// Marker component for static platform entities
+/// Marker for bodies pulled down by their [`Gravity`] and stopped by
+/// platforms, such as the player and crates. Only dynamic bodies get either.
+/// Every entity with a [`Velocity`], a [`BoxCollider`] and gravity becomes
+/// one unless it is a [`KinematicBody`].
+#[derive(Component, Debug, Default, Clone, Copy)]
+pub struct DynamicBody;
+
+/// Marker for bodies that move only as their own systems dictate. Gravity
+/// and platforms leave them alone even when they have [`Gravity`].
+#[derive(Component, Debug, Default, Clone, Copy)]
+pub struct KinematicBody;
+
+/// Marker component for platform entities. Most are static, but a
+/// [`Pushable`] platform moves as a body of its own.
 #[derive(Component, Debug, Default)]
 pub struct Platform;
 
+/// How a [`Platform`] treats a body standing on it
+#[derive(Component, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SurfaceMaterial {
+    /// Grip multiplier. Below 1 is slippery like ice: horizontal speed
+    /// changes gradually instead of instantly. Above 1 is sticky like mud:
+    /// top speed is divided by it.
+    pub friction: f32,
+    /// Share of the landing speed bounced back up, `0` for none
+    pub bounciness: f32,
+    /// Horizontal speed at which standing bodies are carried along
+    pub conveyor: f32,
+}
+
+impl SurfaceMaterial {
+    /// Moves a horizontal speed toward `target` over `delta` seconds with
+    /// this surface's grip
+    pub fn steer(&self, speed: f32, target: f32, delta: f32) -> f32 {
+        let target = target / self.friction.max(1.0);
+        // Share of the difference closed per 1/60 s
+        let grip = self.friction.clamp(0.0, 1.0);
+        let blend = 1.0 - (1.0 - grip).powf(delta * 60.0);
+        speed + (target - speed) * blend
+    }
+}
+
+impl Default for SurfaceMaterial {
+    fn default() -> Self {
+        Self {
+            friction: 1.0,
+            bounciness: 0.0,
+            conveyor: 0.0,
+        }
+    }
+}
+
+/// The [`SurfaceMaterial`] under a grounded entity, default while airborne
+#[derive(Component, Debug, Default, Clone, Copy)]
+pub struct GroundSurface(pub SurfaceMaterial);
+
+/// Makes a [`Platform`] a ramp. Its top edge runs from `left_height` above
+/// the bottom of its [`BoxCollider`] on the left to `right_height` on the
+/// right.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Slope {
+    pub left_height: f32,
+    pub right_height: f32,
+}
+
+impl Slope {
+    pub fn new(left_height: f32, right_height: f32) -> Self {
+        Self {
+            left_height,
+            right_height,
+        }
+    }
+
+    /// Height of the top edge above the bottom, `x` from the left edge of a
+    /// ramp `width` wide
+    pub fn height_at(&self, x: f32, width: f32) -> f32 {
+        let t = (x / width).clamp(0.0, 1.0);
+        self.left_height + (self.right_height - self.left_height) * t
+    }
+
+    /// World height of the top edge at world `x`, for a ramp covering
+    /// `bounds`
+    pub fn surface_at(&self, x: f32, bounds: Rect) -> f32 {
+        bounds.min.y + self.height_at(x - bounds.min.x, bounds.width())
+    }
+
+    /// Steepness of the top edge in radians
+    pub fn angle(&self, width: f32) -> f32 {
+        ((self.right_height - self.left_height).abs() / width).atan()
+    }
+}
+
+/// Steepest slope, in radians, an entity can stand on. On steeper slopes
+/// it slides down.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct MaxSlope(pub f32);
+
+impl Default for MaxSlope {
+    fn default() -> Self {
+        Self(50f32.to_radians())
+    }
+}
+
 /// Box collider for AABB collision detection
 #[derive(Component, Debug, Clone)]
 pub struct BoxCollider {
@@ -105,6 +308,11 @@ impl BoxCollider {
     pub fn new(width: f32, height: f32) -> Self {
         Self { width, height }
     }
+
+    /// World-space bounds of the collider centered on `position`
+    pub fn bounds(&self, position: Vec3) -> Rect {
+        Rect::from_center_size(position.truncate(), Vec2::new(self.width, self.height))
+    }
 }
 
 impl Default for BoxCollider {
@@ -116,11 +324,80 @@ impl Default for BoxCollider {
     }
 }
 
+/// A group of colliders that can be filtered on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum CollisionLayer {
+    Player,
+    Enemy,
+    Obstacle,
+    Platform,
+    Pickup,
+    Projectile,
+    Goal,
+}
+
+impl CollisionLayer {
+    pub const ALL: [CollisionLayer; 7] = [
+        Self::Player,
+        Self::Enemy,
+        Self::Obstacle,
+        Self::Platform,
+        Self::Pickup,
+        Self::Projectile,
+        Self::Goal,
+    ];
+
+    /// The layer's bit in a [`CollisionLayers`] mask
+    pub fn bit(self) -> u32 {
+        1 << self as u32
+    }
+
+    /// Mask with the bits of all `layers` set
+    pub fn mask(layers: &[CollisionLayer]) -> u32 {
+        layers.iter().fold(0, |mask, layer| mask | layer.bit())
+    }
+}
+
+/// Which layers a collider belongs to and which layers it collides with.
+///
+/// Two colliders interact only when each one's filter includes a layer the
+/// other is a member of. Colliders without this component collide with
+/// everything.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CollisionLayers {
+    pub membership: u32,
+    pub filter: u32,
+}
+
+impl CollisionLayers {
+    pub fn new(membership: &[CollisionLayer], filter: &[CollisionLayer]) -> Self {
+        Self {
+            membership: CollisionLayer::mask(membership),
+            filter: CollisionLayer::mask(filter),
+        }
+    }
+
+    pub fn interacts_with(&self, other: &CollisionLayers) -> bool {
+        self.filter & other.membership != 0 && other.filter & self.membership != 0
+    }
+}
+
+impl Default for CollisionLayers {
+    fn default() -> Self {
+        Self {
+            membership: u32::MAX,
+            filter: u32::MAX,
+        }
+    }
+}
+
 /// Jump configuration component for Mario-like jumping
 #[derive(Component, Debug, Clone)]
 pub struct JumpConfig {
     pub jump_velocity: f32,
     pub jump_cut_multiplier: f32,
+    /// Upward velocity of each swim stroke
+    pub stroke_velocity: f32,
 }
 
 impl Default for JumpConfig {
@@ -128,6 +405,7 @@ impl Default for JumpConfig {
         Self {
             jump_velocity: 450.0,
             jump_cut_multiplier: 0.5,
+            stroke_velocity: 250.0,
         }
     }
 }
@@ -199,6 +477,11 @@ impl Default for CameraFollow {
 }
 
 impl CameraFollow {
+    /// Minimum smoothing factor (instant follow)
+    pub const MIN_SMOOTHING: f32 = 0.01;
+    /// Maximum smoothing factor (slowest follow)
+    pub const MAX_SMOOTHING: f32 = 1.0;
+
     pub fn new(target: Entity) -> Self {
         Self {
             target: Some(target),
@@ -212,7 +495,7 @@ impl CameraFollow {
     }
 
     pub fn with_smoothing(mut self, smoothing: f32) -> Self {
-        self.smoothing = smoothing.clamp(MIN_SMOOTHING, MAX_SMOOTHING);
+        self.smoothing = smoothing.clamp(Self::MIN_SMOOTHING, Self::MAX_SMOOTHING);
         self
     }
 }
@@ -251,6 +534,407 @@ impl DamageOnContact {
     }
 }
 
+/// Marker component for the level goal (finish flag)
+#[derive(Component, Debug, Default)]
+pub struct LevelGoal;
+
+/// Component for entities spawned from level data, holding the level they
+/// belong to so they can be despawned when that level is unloaded
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct LevelEntity(pub AssetId<LevelData>);
+
+/// Marker component for platforms that can be jumped through from below
+/// and dropped through while holding down
+#[derive(Component, Debug, Default)]
+pub struct OneWayPlatform;
+
+/// Component tracking if an entity is dropping through one-way platforms
+#[derive(Component, Debug, Default, Clone)]
+pub struct DropThrough(pub bool);
+
+/// How an entity is currently moving
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum MovementMode {
+    /// Walking and jumping under gravity
+    #[default]
+    Normal,
+    /// Holding on to a [`Climbable`], moving up and down without gravity
+    Climbing,
+    /// Inside a [`WaterVolume`], rising with each jump stroke
+    Swimming,
+}
+
+impl MovementMode {
+    /// Whether gravity pulls the entity down in this mode
+    pub fn has_gravity(self) -> bool {
+        self != Self::Climbing
+    }
+}
+
+/// Trigger volume the player can climb, such as a ladder or vines. Its
+/// area is the entity's [`BoxCollider`].
+#[derive(Component, Debug, Default)]
+pub struct Climbable;
+
+/// Trigger volume of water. Its area is the entity's [`BoxCollider`].
+#[derive(Component, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WaterVolume {
+    /// Share of a body's [`Gravity`] that still pulls it down
+    pub gravity_scale: f32,
+    /// Upward acceleration on a fully submerged body, scaled by how much
+    /// of it is under the surface
+    pub buoyancy: f32,
+    /// Rate at which velocity dies down, per second. Movers that set their
+    /// own speed move at `1 / (1 + drag)` of it.
+    pub drag: f32,
+}
+
+impl Default for WaterVolume {
+    fn default() -> Self {
+        Self {
+            gravity_scale: 0.3,
+            buoyancy: 250.0,
+            drag: 1.0,
+        }
+    }
+}
+
+/// Added to an entity whose center is inside a [`WaterVolume`]
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Submerged {
+    pub water: WaterVolume,
+    /// Share of the entity's height below the surface, `1.0` when its head
+    /// is under water
+    pub depth: f32,
+}
+
+impl Submerged {
+    /// Speed multiplier for movers that set their own speed
+    pub fn speed_scale(&self) -> f32 {
+        1.0 / (1.0 + self.water.drag)
+    }
+}
+
+/// Air left while fully under water. Once it runs out the entity drowns,
+/// losing [`Health`] every second until it surfaces.
+#[derive(Component, Debug, Clone)]
+pub struct Breath {
+    /// Seconds of air left
+    pub remaining: f32,
+    pub max: f32,
+    /// Damage per second while out of air
+    pub drowning_damage: f32,
+}
+
+impl Breath {
+    /// Seconds of air regained per second above water
+    pub const REFILL_RATE: f32 = 4.0;
+
+    pub fn new(max: f32, drowning_damage: f32) -> Self {
+        Self {
+            remaining: max,
+            max,
+            drowning_damage,
+        }
+    }
+
+    /// Uses up or regains air over `delta` seconds, returning the drowning
+    /// damage taken
+    pub fn tick(&mut self, delta: f32, under_water: bool) -> f32 {
+        if !under_water {
+            self.remaining = (self.remaining + Self::REFILL_RATE * delta).min(self.max);
+            return 0.0;
+        }
+        let out_of_air = (delta - self.remaining).max(0.0);
+        self.remaining = (self.remaining - delta).max(0.0);
+        out_of_air * self.drowning_damage
+    }
+}
+
+/// Sensor volume that reports colliders entering and leaving it with
+/// [`TriggerEntered`](crate::events::TriggerEntered) and
+/// [`TriggerExited`](crate::events::TriggerExited) events. Its area is the
+/// entity's [`BoxCollider`], or its [`Collider`] shape when it has one.
+///
+/// Triggers never collide. The filter of their [`CollisionLayers`] picks
+/// which layers they detect; without one they detect everything but
+/// static platforms.
+#[derive(Component, Debug, Clone, Default)]
+pub struct Trigger {
+    occupants: Vec<Entity>,
+}
+
+impl Trigger {
+    /// Entities inside the trigger, in the order they were found
+    pub fn occupants(&self) -> &[Entity] {
+        &self.occupants
+    }
+
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.occupants.contains(&entity)
+    }
+
+    /// Replaces the occupants with `current`, returning the entities that
+    /// entered and those that left
+    pub fn update(&mut self, current: Vec<Entity>) -> (Vec<Entity>, Vec<Entity>) {
+        let entered = current
+            .iter()
+            .filter(|entity| !self.occupants.contains(entity))
+            .copied()
+            .collect();
+        let exited = self
+            .occupants
+            .iter()
+            .filter(|entity| !current.contains(entity))
+            .copied()
+            .collect();
+        self.occupants = current;
+        (entered, exited)
+    }
+}
+
+/// Area that hurts or kills the player on entering, then respawns them.
+/// Detected by the entity's [`Trigger`].
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq)]
+pub struct KillZone {
+    /// Damage dealt, or `None` to kill outright
+    pub damage: Option<f32>,
+}
+
+/// A body the player can shove sideways, such as a crate. Pushables are
+/// [`Platform`]s with [`Gravity`], so they can be stood on and land on
+/// other platforms.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct Pushable {
+    /// Pushed speed is the pusher's speed divided by the mass, so a mass of
+    /// 1 or less keeps pace with the pusher
+    pub mass: f32,
+}
+
+impl Default for Pushable {
+    fn default() -> Self {
+        Self { mass: 1.0 }
+    }
+}
+
+impl Pushable {
+    /// Speed this body moves at when pushed at `speed`
+    pub fn push_speed(&self, speed: f32) -> f32 {
+        speed / self.mass.max(1.0)
+    }
+}
+
+/// Plate held down while its [`Trigger`] has anything on it, such as the
+/// player or a pushed crate
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PressurePlate {
+    pub pressed: bool,
+}
+
+/// Marker component for enemy entities
+#[derive(Component, Debug, Default)]
+pub struct Enemy;
+
+/// Patrol component moving an entity back and forth along the X axis
+#[derive(Component, Debug, Clone)]
+pub struct Patrol {
+    /// Center of the patrol route
+    pub origin_x: f32,
+    /// Distance travelled either side of the origin
+    pub distance: f32,
+    pub speed: f32,
+    /// Current direction of travel (-1.0 or 1.0)
+    pub direction: f32,
+}
+
+impl Patrol {
+    pub fn new(origin_x: f32, distance: f32, speed: f32) -> Self {
+        Self {
+            origin_x,
+            distance,
+            speed,
+            direction: 1.0,
+        }
+    }
+}
+
+/// Bobs an entity up and down along a sine wave on top of its other movement
+#[derive(Component, Debug, Clone)]
+pub struct SineBob {
+    /// Peak vertical offset in pixels
+    pub amplitude: f32,
+    /// Oscillations per second
+    pub frequency: f32,
+    /// Seconds since the bobbing started
+    pub elapsed: f32,
+}
+
+impl SineBob {
+    pub fn new(amplitude: f32, frequency: f32) -> Self {
+        Self {
+            amplitude,
+            frequency,
+            elapsed: 0.0,
+        }
+    }
+
+    /// Vertical offset from the unbobbed position after `elapsed` seconds
+    pub fn offset(&self, elapsed: f32) -> f32 {
+        self.amplitude * (std::f32::consts::TAU * self.frequency * elapsed).sin()
+    }
+}
+
+/// Turns an entity's [`AutoMove`] direction toward the player
+#[derive(Component, Debug, Clone)]
+pub struct Homing {
+    /// Maximum turn rate in radians per second
+    pub turn_rate: f32,
+}
+
+impl Homing {
+    pub fn new(turn_rate: f32) -> Self {
+        Self { turn_rate }
+    }
+
+    /// Rotates `direction` toward `to_target`, by at most the turn rate
+    pub fn steer(&self, direction: Vec2, to_target: Vec2, delta: f32) -> Vec2 {
+        if to_target == Vec2::ZERO {
+            return direction;
+        }
+        let angle = direction.angle_between(to_target);
+        let max_turn = self.turn_rate * delta;
+        Vec2::from_angle(angle.clamp(-max_turn, max_turn)).rotate(direction)
+    }
+}
+
+/// Falls under gravity and bounces off the top of platforms
+#[derive(Component, Debug, Clone)]
+pub struct Bounce {
+    pub velocity_y: f32,
+    /// Downward acceleration in pixels per second squared
+    pub gravity: f32,
+    /// Fraction of the vertical speed kept on each bounce
+    pub restitution: f32,
+}
+
+impl Bounce {
+    pub fn new(gravity: f32, restitution: f32) -> Self {
+        Self {
+            velocity_y: 0.0,
+            gravity,
+            restitution,
+        }
+    }
+}
+
+/// Circles an entity around a point that travels with its other movement
+#[derive(Component, Debug, Clone)]
+pub struct Orbit {
+    pub radius: f32,
+    /// Radians per second, positive is counter-clockwise
+    pub angular_speed: f32,
+    /// Current angle around the center
+    pub angle: f32,
+}
+
+impl Orbit {
+    pub fn new(radius: f32, angular_speed: f32) -> Self {
+        Self {
+            radius,
+            angular_speed,
+            angle: 0.0,
+        }
+    }
+
+    /// Position relative to the orbit center at `angle`
+    pub fn offset(&self, angle: f32) -> Vec2 {
+        Vec2::from_angle(angle) * self.radius
+    }
+}
+
+/// Moves an entity along a Catmull-Rom spline through `points`, relative to
+/// where it started
+#[derive(Component, Debug, Clone)]
+pub struct SplinePath {
+    pub points: Vec<Vec2>,
+    /// Approximate speed along the path in pixels per second
+    pub speed: f32,
+    /// Starts over from the first point after reaching the last
+    pub looping: bool,
+    /// Position along the path, in segments
+    pub progress: f32,
+}
+
+impl SplinePath {
+    pub fn new(points: Vec<Vec2>, speed: f32, looping: bool) -> Self {
+        Self {
+            points,
+            speed,
+            looping,
+            progress: 0.0,
+        }
+    }
+
+    /// Number of segments between points
+    pub fn segments(&self) -> usize {
+        self.points.len().saturating_sub(1)
+    }
+
+    /// Direction the path is heading at its last point
+    pub fn end_direction(&self) -> Vec2 {
+        match self.points.as_slice() {
+            [.., before, last] => (*last - *before).normalize_or_zero(),
+            _ => Vec2::ZERO,
+        }
+    }
+
+    /// Point on the path at `progress` segments, clamped to its ends
+    pub fn sample(&self, progress: f32) -> Vec2 {
+        let segments = self.segments();
+        if segments == 0 {
+            return self.points.first().copied().unwrap_or_default();
+        }
+        let progress = progress.clamp(0.0, segments as f32);
+        let segment = (progress.floor() as usize).min(segments - 1);
+        let t = progress - segment as f32;
+
+        // End points are repeated so the curve passes through every point
+        let point = |i: isize| self.points[i.clamp(0, segments as isize) as usize];
+        let i = segment as isize;
+        let (p0, p1, p2, p3) = (point(i - 1), point(i), point(i + 1), point(i + 2));
+
+        0.5 * (2.0 * p1
+            + (p2 - p0) * t
+            + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t * t
+            + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t * t * t)
+    }
+}
+
+/// Collectible component awarding points when picked up
+#[derive(Component, Debug, Clone)]
+pub struct Collectible {
+    pub points: u32,
+}
+
+impl Default for Collectible {
+    fn default() -> Self {
+        Self { points: 50 }
+    }
+}
+
+/// Marker component for entities waiting in an [`EntityPool`](crate::resources::EntityPool)
+#[derive(Component, Debug, Default)]
+pub struct Pooled;
+
+/// Marker component for the parent entity of a level's tile sprites
+#[derive(Component, Debug, Default)]
+pub struct Tilemap;
+
+/// Marker component for the level results screen UI
+#[derive(Component, Debug, Default)]
+pub struct ResultsScreen;
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -277,6 +961,51 @@ mod tests {
         assert_eq!(health.current, 80.0);
     }
 
+    #[test]
+    fn test_fitted_shapes_stay_inside_their_box() {
+        let size = Vec2::new(40.0, 60.0);
+        for kind in [ShapeKind::Box, ShapeKind::Circle, ShapeKind::Capsule] {
+            let shape = Collider::fitted(kind, size);
+            assert!(shape.bounding_size().cmple(size).all(), "{kind:?}");
+        }
+        assert_eq!(
+            Collider::fitted(ShapeKind::Capsule, size),
+            Collider::capsule(20.0, 10.0)
+        );
+        let turned = Collider::obb(10.0, 20.0, std::f32::consts::FRAC_PI_2);
+        assert!(turned
+            .bounding_size()
+            .abs_diff_eq(Vec2::new(20.0, 10.0), 1e-4));
+    }
+
+    #[test]
+    fn test_slope_height_and_angle() {
+        let ramp = Slope::new(0.0, 100.0);
+        assert_eq!(ramp.height_at(25.0, 100.0), 25.0);
+        // Clamped to the ends of the ramp
+        assert_eq!(ramp.height_at(-10.0, 100.0), 0.0);
+        assert_eq!(ramp.height_at(150.0, 100.0), 100.0);
+        assert!((ramp.angle(100.0) - std::f32::consts::FRAC_PI_4).abs() < 1e-6);
+        assert_eq!(Slope::new(30.0, 30.0).angle(100.0), 0.0);
+    }
+
+    #[test]
+    fn test_collision_layers_need_both_filters() {
+        use CollisionLayer::*;
+        let player = CollisionLayers::new(&[Player], &[Enemy, Pickup]);
+        let enemy = CollisionLayers::new(&[Enemy], &[Player, Platform]);
+        let pickup = CollisionLayers::new(&[Pickup], &[Player]);
+        let platform = CollisionLayers::new(&[Platform], &[Enemy]);
+
+        assert!(player.interacts_with(&enemy));
+        assert!(pickup.interacts_with(&player));
+        assert!(enemy.interacts_with(&platform));
+        // The player does not list platforms, so the platform's filter alone is not enough
+        assert!(!player.interacts_with(&platform));
+        assert!(!pickup.interacts_with(&enemy));
+        assert!(CollisionLayers::default().interacts_with(&pickup));
+    }
+
     #[test]
     fn test_health_heal_clamps_to_max() {
         let mut health = Health::new(100.0);
@@ -306,6 +1035,57 @@ mod tests {
         assert_eq!(gravity.0, 980.0);
     }
 
+    #[test]
+    fn test_surface_material_grip() {
+        let normal = SurfaceMaterial::default();
+        assert_eq!(normal.steer(0.0, 200.0, 1.0 / 60.0), 200.0);
+
+        // Ice closes a tenth of the gap each frame
+        let ice = SurfaceMaterial {
+            friction: 0.1,
+            ..default()
+        };
+        assert!((ice.steer(0.0, 200.0, 1.0 / 60.0) - 20.0).abs() < 1e-3);
+
+        // Mud halves the top speed
+        let mud = SurfaceMaterial {
+            friction: 2.0,
+            ..default()
+        };
+        assert_eq!(mud.steer(200.0, 200.0, 1.0 / 60.0), 100.0);
+    }
+
+    #[test]
+    fn test_heavier_pushables_move_slower() {
+        assert_eq!(Pushable { mass: 0.5 }.push_speed(200.0), 200.0);
+        assert_eq!(Pushable::default().push_speed(200.0), 200.0);
+        assert_eq!(Pushable { mass: 4.0 }.push_speed(200.0), 50.0);
+    }
+
+    #[test]
+    fn test_trigger_reports_entered_and_exited() {
+        let [a, b, c] = [1, 2, 3].map(Entity::from_raw);
+        let mut trigger = Trigger::default();
+
+        assert_eq!(trigger.update(vec![a, b]), (vec![a, b], vec![]));
+        // Staying inside is not reported again
+        assert_eq!(trigger.update(vec![b, c]), (vec![c], vec![a]));
+        assert!(trigger.contains(c) && !trigger.contains(a));
+        assert_eq!(trigger.update(vec![]), (vec![], vec![b, c]));
+    }
+
+    #[test]
+    fn test_breath_runs_out_then_drowns() {
+        let mut breath = Breath::new(1.0, 20.0);
+        assert_eq!(breath.tick(0.5, true), 0.0);
+        // Half a second of air left, the rest of the tick is spent drowning
+        assert_eq!(breath.tick(1.0, true), 10.0);
+        assert_eq!(breath.remaining, 0.0);
+
+        assert_eq!(breath.tick(0.125, false), 0.0);
+        assert_eq!(breath.remaining, 0.5);
+    }
+
     #[test]
     fn test_grounded_default() {
         let grounded = Grounded::default();
@@ -361,6 +1141,50 @@ mod tests {
         assert_eq!(auto_move.speed, 100.0);
     }
 
+    #[test]
+    fn test_homing_turn_rate_is_limited() {
+        let homing = Homing::new(std::f32::consts::FRAC_PI_2);
+        let left = Vec2::new(-1.0, 0.0);
+
+        // A target straight up is a quarter turn away, half of which is
+        // allowed in half a second
+        let turned = homing.steer(left, Vec2::new(0.0, 100.0), 0.5);
+        assert!((left.angle_between(turned).abs() - std::f32::consts::FRAC_PI_4).abs() < 1e-5);
+        assert!(turned.y > 0.0);
+
+        let turned = homing.steer(left, Vec2::new(0.0, 100.0), 2.0);
+        assert!(turned.abs_diff_eq(Vec2::Y, 1e-5));
+    }
+
+    #[test]
+    fn test_spline_path_passes_through_points() {
+        let path = SplinePath::new(
+            vec![Vec2::ZERO, Vec2::new(100.0, 50.0), Vec2::new(200.0, 0.0)],
+            100.0,
+            false,
+        );
+        assert_eq!(path.segments(), 2);
+        assert_eq!(path.sample(0.0), Vec2::ZERO);
+        assert!(path.sample(1.0).abs_diff_eq(Vec2::new(100.0, 50.0), 1e-4));
+        assert!(path.sample(5.0).abs_diff_eq(Vec2::new(200.0, 0.0), 1e-4));
+        assert!(path.sample(0.5).y > 0.0);
+        assert!(path
+            .end_direction()
+            .abs_diff_eq(Vec2::new(100.0, -50.0).normalize(), 1e-6));
+    }
+
+    #[test]
+    fn test_sine_bob_and_orbit_offsets() {
+        let bob = SineBob::new(20.0, 0.5);
+        assert_eq!(bob.offset(0.0), 0.0);
+        assert!((bob.offset(0.5) - 20.0).abs() < 1e-4);
+
+        let orbit = Orbit::new(30.0, 1.0);
+        assert!(orbit
+            .offset(std::f32::consts::FRAC_PI_2)
+            .abs_diff_eq(Vec2::new(0.0, 30.0), 1e-4));
+    }
+
     #[test]
     fn test_camera_follow_default() {
         let follow = CameraFollow::default();
@@ -380,4 +1204,12 @@ mod tests {
         let damage = DamageOnContact::new(25.0);
         assert_eq!(damage.damage, 25.0);
     }
+
+    #[test]
+    fn test_patrol_new() {
+        let patrol = Patrol::new(100.0, 50.0, 80.0);
+        assert_eq!(patrol.origin_x, 100.0);
+        assert_eq!(patrol.distance, 50.0);
+        assert_eq!(patrol.direction, 1.0);
+    }
 }
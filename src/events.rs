@@ -0,0 +1,54 @@
+//! Game Events
+//!
+//! This module contains events sent between systems.
+
+use bevy::prelude::*;
+
+/// Two colliders whose [`CollisionLayers`](crate::components::CollisionLayers)
+/// interact started or kept overlapping this frame. Each pair is sent once.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct Collision {
+    pub a: Entity,
+    pub b: Entity,
+    /// Unit vector pointing from `b` toward `a`
+    pub normal: Vec2,
+    /// How far `a` must move along `normal` to separate them
+    pub depth: f32,
+}
+
+impl Collision {
+    /// The pair ordered so the first entity satisfies `first`, or `None`
+    /// if neither does
+    pub fn pair_with(&self, first: impl Fn(Entity) -> bool) -> Option<(Entity, Entity)> {
+        if first(self.a) {
+            Some((self.a, self.b))
+        } else if first(self.b) {
+            Some((self.b, self.a))
+        } else {
+            None
+        }
+    }
+}
+
+/// `entity` started overlapping `trigger`, an entity with a
+/// [`Trigger`](crate::components::Trigger)
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TriggerEntered {
+    pub trigger: Entity,
+    pub entity: Entity,
+}
+
+/// `entity` stopped overlapping `trigger`, or was despawned while inside it
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TriggerExited {
+    pub trigger: Entity,
+    pub entity: Entity,
+}
+
+/// `entity` is squeezed between `solids`, which leave it too little room to
+/// be pushed out
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Crushed {
+    pub entity: Entity,
+    pub solids: [Entity; 2],
+}
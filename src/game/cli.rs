@@ -0,0 +1,133 @@
+//! Command line arguments
+
+use thiserror::Error;
+
+use super::config::{ConfigOverride, ConfigOverrideError};
+
+/// Usage text printed for `--help` and argument errors
+pub const USAGE: &str = "\
+Usage: template-bevy [OPTIONS]
+
+Options:
+  --config <PATH>          Config file inside the assets directory [default: game.config.ron]
+  --set <SECTION.FIELD=VALUE>
+                           Override a config value, e.g. --set obstacles.spawn_interval=1.5
+  --seed <SEED>            Seed for gameplay randomness, making runs reproducible
+  -h, --help               Print this help";
+
+/// Parsed command line arguments
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CliArgs {
+    /// Config asset path given with `--config`
+    pub config: Option<String>,
+    /// Config overrides given with `--set`, in order
+    pub overrides: Vec<ConfigOverride>,
+    /// Seed given with `--seed`, taking precedence over the config file
+    pub seed: Option<u64>,
+    pub help: bool,
+}
+
+/// Errors produced while parsing command line arguments
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum CliError {
+    #[error("unknown argument \"{0}\"")]
+    UnknownArgument(String),
+    #[error("{0} expects a value")]
+    MissingValue(String),
+    #[error("invalid seed \"{0}\", expected a non-negative integer")]
+    InvalidSeed(String),
+    #[error(transparent)]
+    Override(#[from] ConfigOverrideError),
+}
+
+impl CliArgs {
+    /// Parses arguments, excluding the program name
+    pub fn parse<I, S>(args: I) -> Result<Self, CliError>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter().map(Into::into);
+
+        while let Some(arg) = args.next() {
+            // Accept both `--flag value` and `--flag=value`
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => {
+                    (flag.to_string(), Some(value.to_string()))
+                }
+                _ => (arg, None),
+            };
+            let mut value = || {
+                inline_value
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| CliError::MissingValue(flag.clone()))
+            };
+
+            match flag.as_str() {
+                "--config" => parsed.config = Some(value()?),
+                "--set" => parsed.overrides.push(value()?.parse()?),
+                "--seed" => {
+                    let seed = value()?;
+                    parsed.seed = Some(seed.parse().map_err(|_| CliError::InvalidSeed(seed))?);
+                }
+                "-h" | "--help" => parsed.help = true,
+                _ => return Err(CliError::UnknownArgument(flag)),
+            }
+        }
+
+        Ok(parsed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config_and_overrides() {
+        let args = CliArgs::parse([
+            "--config",
+            "configs/hard.config.ron",
+            "--set",
+            "obstacles.spawn_interval=1.5",
+            "--set=scoring.enemy_stomp_points=200",
+            "--seed",
+            "1234",
+        ])
+        .unwrap();
+
+        assert_eq!(args.seed, Some(1234));
+        assert_eq!(args.config.as_deref(), Some("configs/hard.config.ron"));
+        assert_eq!(
+            args.overrides,
+            vec![
+                ConfigOverride {
+                    key: "obstacles.spawn_interval".to_string(),
+                    value: "1.5".to_string(),
+                },
+                ConfigOverride {
+                    key: "scoring.enemy_stomp_points".to_string(),
+                    value: "200".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_unknown_and_incomplete_arguments() {
+        assert_eq!(
+            CliArgs::parse(["--fullscreen"]),
+            Err(CliError::UnknownArgument("--fullscreen".to_string()))
+        );
+        assert_eq!(
+            CliArgs::parse(["--config"]),
+            Err(CliError::MissingValue("--config".to_string()))
+        );
+        assert_eq!(
+            CliArgs::parse(["--seed", "-1"]),
+            Err(CliError::InvalidSeed("-1".to_string()))
+        );
+    }
+}
@@ -0,0 +1,686 @@
+//! Runtime game configuration loaded from `.config.ron` files
+//!
+//! The [`GameConfig`] resource holds the values systems read every frame.
+//! It is loaded from `assets/game.config.ron` at startup, can be overridden
+//! from the command line (`--set obstacles.spawn_interval=1.5`), and is also
+//! an asset, so editing the file while the game runs (with the `hot_reload`
+//! feature) replaces the resource and re-applies it to live entities.
+//! Missing sections and fields keep their defaults.
+
+use std::str::FromStr;
+
+use bevy::asset::io::file::FileAssetReader;
+use bevy::asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::difficulty::DifficultyConfig;
+use super::waves::WaveConfig;
+use crate::components::{CameraFollow, CollisionLayer, CollisionLayers, ShapeKind};
+use crate::resources::Difficulty;
+
+/// Game configuration
+#[derive(Asset, Resource, TypePath, Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameConfig {
+    /// Seed for all gameplay randomness. `None` picks a random seed per run.
+    pub seed: Option<u64>,
+    pub player: PlayerConfig,
+    pub obstacles: ObstacleConfig,
+    pub difficulty: DifficultyConfig,
+    pub waves: WaveConfig,
+    pub scoring: ScoringConfig,
+    pub collision: CollisionConfig,
+    pub camera: CameraConfig,
+    pub window: WindowConfig,
+}
+
+/// Player movement and health
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlayerConfig {
+    pub speed: f32,
+    pub max_health: f32,
+    /// Downward acceleration in pixels per second squared
+    pub gravity: f32,
+    pub jump_velocity: f32,
+    /// Vertical velocity multiplier applied when jump is released early
+    pub jump_cut_multiplier: f32,
+    /// Collision shape fitted to the player's box. A capsule slides off
+    /// ledges instead of standing on their corners. Applies when the player
+    /// spawns.
+    pub shape: ShapeKind,
+    /// Steepest ramp, in degrees, the player can walk on without sliding
+    pub max_slope_angle: f32,
+    /// Upward velocity of each jump stroke while swimming
+    pub swim_stroke_velocity: f32,
+    /// Seconds the player can stay fully under water before drowning, or
+    /// `None` to breathe under water. Turning it on or off applies when the
+    /// player spawns.
+    pub breath: Option<f32>,
+    /// Damage per second while drowning
+    pub drowning_damage: f32,
+    /// Damage for leaving the level bounds before respawning, or `None` to
+    /// die
+    pub out_of_bounds_damage: Option<f32>,
+    /// Damage for being crushed between solids before respawning, or `None`
+    /// to die
+    pub crush_damage: Option<f32>,
+}
+
+impl Default for PlayerConfig {
+    fn default() -> Self {
+        Self {
+            speed: 250.0,
+            max_health: 100.0,
+            gravity: 980.0,
+            jump_velocity: 450.0,
+            jump_cut_multiplier: 0.5,
+            shape: ShapeKind::Box,
+            max_slope_angle: 50.0,
+            swim_stroke_velocity: 250.0,
+            breath: Some(8.0),
+            drowning_damage: 20.0,
+            out_of_bounds_damage: None,
+            crush_damage: None,
+        }
+    }
+}
+
+/// Obstacle spawning
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ObstacleConfig {
+    /// Seconds between obstacle spawns
+    pub spawn_interval: f32,
+    /// Screen edges random obstacles spawn from. Waves always spawn from the
+    /// right edge.
+    pub spawn_edges: Vec<ScreenEdge>,
+    /// Distance outside the camera view at which obstacles spawn
+    pub spawn_margin: f32,
+    /// Height range for obstacles spawning from the left or right edge,
+    /// relative to the center of the view
+    pub spawn_y_min: f32,
+    pub spawn_y_max: f32,
+    pub width_min: f32,
+    pub width_max: f32,
+    pub height_min: f32,
+    pub height_max: f32,
+    pub speed_min: f32,
+    pub speed_max: f32,
+    /// Distance outside the camera view beyond which obstacles moving away
+    /// from it are despawned
+    pub despawn_margin: f32,
+    pub damage: f32,
+}
+
+/// An edge of the camera view
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ScreenEdge {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+impl ScreenEdge {
+    /// Outward direction of the edge
+    pub fn normal(self) -> Vec2 {
+        match self {
+            Self::Left => Vec2::NEG_X,
+            Self::Right => Vec2::X,
+            Self::Top => Vec2::Y,
+            Self::Bottom => Vec2::NEG_Y,
+        }
+    }
+}
+
+impl Default for ObstacleConfig {
+    fn default() -> Self {
+        Self {
+            spawn_interval: 2.0,
+            spawn_edges: vec![ScreenEdge::Right],
+            spawn_margin: 60.0,
+            spawn_y_min: -200.0,
+            spawn_y_max: 150.0,
+            width_min: 30.0,
+            width_max: 60.0,
+            height_min: 30.0,
+            height_max: 60.0,
+            speed_min: 100.0,
+            speed_max: 250.0,
+            despawn_margin: 160.0,
+            damage: 10.0,
+        }
+    }
+}
+
+impl ObstacleConfig {
+    /// Returns these settings scaled by the current difficulty
+    pub fn scaled(&self, difficulty: &Difficulty) -> Self {
+        Self {
+            spawn_interval: self.spawn_interval / difficulty.spawn_rate,
+            width_min: self.width_min * difficulty.size,
+            width_max: self.width_max * difficulty.size,
+            height_min: self.height_min * difficulty.size,
+            height_max: self.height_max * difficulty.size,
+            speed_min: self.speed_min * difficulty.speed,
+            speed_max: self.speed_max * difficulty.speed,
+            damage: self.damage * difficulty.damage,
+            ..self.clone()
+        }
+    }
+}
+
+/// Points awarded during and after a level
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScoringConfig {
+    /// Points awarded for surviving obstacle collision
+    pub obstacle_survive_points: u32,
+    /// Points awarded for stomping on an enemy
+    pub enemy_stomp_points: u32,
+    /// Bonus points awarded per second left on the level timer
+    pub time_bonus_per_second: u32,
+    /// Bonus points awarded per point of remaining health
+    pub health_bonus_per_point: u32,
+}
+
+impl Default for ScoringConfig {
+    fn default() -> Self {
+        Self {
+            obstacle_survive_points: 10,
+            enemy_stomp_points: 100,
+            time_bonus_per_second: 10,
+            health_bonus_per_point: 5,
+        }
+    }
+}
+
+/// What each collision layer collides with. A pair of layers only
+/// collides when both list each other.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CollisionConfig {
+    pub player: Vec<CollisionLayer>,
+    pub enemy: Vec<CollisionLayer>,
+    pub obstacle: Vec<CollisionLayer>,
+    pub platform: Vec<CollisionLayer>,
+    pub pickup: Vec<CollisionLayer>,
+    pub projectile: Vec<CollisionLayer>,
+    pub goal: Vec<CollisionLayer>,
+}
+
+impl Default for CollisionConfig {
+    fn default() -> Self {
+        use CollisionLayer::*;
+        Self {
+            player: vec![Enemy, Obstacle, Platform, Pickup, Goal],
+            enemy: vec![Player, Platform],
+            obstacle: vec![Player, Platform],
+            platform: vec![Player, Enemy, Obstacle, Platform, Pickup, Projectile],
+            pickup: vec![Player, Platform],
+            projectile: vec![Enemy, Platform],
+            goal: vec![Player],
+        }
+    }
+}
+
+impl CollisionConfig {
+    /// Layers that `layer` collides with
+    pub fn filter(&self, layer: CollisionLayer) -> &[CollisionLayer] {
+        match layer {
+            CollisionLayer::Player => &self.player,
+            CollisionLayer::Enemy => &self.enemy,
+            CollisionLayer::Obstacle => &self.obstacle,
+            CollisionLayer::Platform => &self.platform,
+            CollisionLayer::Pickup => &self.pickup,
+            CollisionLayer::Projectile => &self.projectile,
+            CollisionLayer::Goal => &self.goal,
+        }
+    }
+
+    /// [`CollisionLayers`] for a collider on `layer`
+    pub fn layers(&self, layer: CollisionLayer) -> CollisionLayers {
+        self.layers_for(layer.bit())
+    }
+
+    /// [`CollisionLayers`] for a collider in every layer of the `membership`
+    /// mask, colliding with whatever any of those layers collides with
+    pub fn layers_for(&self, membership: u32) -> CollisionLayers {
+        let filter = CollisionLayer::ALL
+            .into_iter()
+            .filter(|layer| membership & layer.bit() != 0)
+            .fold(0, |filter, layer| {
+                filter | CollisionLayer::mask(self.filter(layer))
+            });
+        CollisionLayers { membership, filter }
+    }
+}
+
+/// Camera follow behaviour
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CameraConfig {
+    /// Framerate the smoothing factor is expressed for
+    pub target_framerate: f32,
+    /// Smoothing factor (0.0 = instant, 1.0 = very smooth/slow)
+    pub smoothing: f32,
+    /// Offset of the camera from the player
+    pub offset: Vec2,
+}
+
+impl Default for CameraConfig {
+    fn default() -> Self {
+        Self {
+            target_framerate: 60.0,
+            smoothing: 0.05,
+            offset: Vec2::new(0.0, 50.0),
+        }
+    }
+}
+
+/// Primary window settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WindowConfig {
+    pub title: String,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        Self {
+            title: "2D Mario-Style Platformer".to_string(),
+            width: 1280.0,
+            height: 720.0,
+        }
+    }
+}
+
+impl WindowConfig {
+    /// Creates the primary window described by this config
+    pub fn to_window(&self) -> Window {
+        Window {
+            title: self.title.clone(),
+            resolution: (self.width, self.height).into(),
+            ..default()
+        }
+    }
+}
+
+/// A config value outside its valid range
+#[derive(Debug, Clone, PartialEq, Error)]
+#[error("{entry}: {reason}")]
+pub struct ConfigValidationError {
+    /// Path to the value, e.g. `obstacles.speed_min`
+    pub entry: String,
+    pub reason: String,
+}
+
+/// A `section.field=value` override given on the command line
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigOverride {
+    pub key: String,
+    pub value: String,
+}
+
+impl FromStr for ConfigOverride {
+    type Err = ConfigOverrideError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let (key, value) = text
+            .split_once('=')
+            .ok_or_else(|| ConfigOverrideError::MissingValue(text.to_string()))?;
+        Ok(Self {
+            key: key.trim().to_string(),
+            value: value.trim().to_string(),
+        })
+    }
+}
+
+/// Errors produced while applying a config override
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum ConfigOverrideError {
+    #[error("expected section.field=value, got \"{0}\"")]
+    MissingValue(String),
+    #[error("unknown config key \"{0}\"")]
+    UnknownKey(String),
+    #[error("invalid value \"{value}\" for {key}: {reason}")]
+    InvalidValue {
+        key: String,
+        value: String,
+        reason: String,
+    },
+}
+
+impl GameConfig {
+    /// Parses a config from RON text
+    pub fn from_ron(text: &str) -> Result<Self, ron::error::SpannedError> {
+        ron::from_str(text)
+    }
+
+    /// Reads and validates a config file from the assets directory,
+    /// applying `overrides` on top of it
+    pub fn load(asset_path: &str, overrides: &[ConfigOverride]) -> Result<Self, ConfigLoadError> {
+        let path = FileAssetReader::new("assets").root_path().join(asset_path);
+        Self::from_ron(&std::fs::read_to_string(path)?)?.with_overrides(overrides)
+    }
+
+    /// This config with `overrides` applied on top, validated, since an
+    /// override can leave an otherwise valid file inconsistent
+    pub fn with_overrides(mut self, overrides: &[ConfigOverride]) -> Result<Self, ConfigLoadError> {
+        self.apply_overrides(overrides)?;
+        self.validate()?;
+        Ok(self)
+    }
+
+    /// Sets the value at a dotted key such as `obstacles.spawn_interval`.
+    /// Values are parsed as JSON, falling back to a plain string.
+    pub fn apply_override(&mut self, key: &str, value: &str) -> Result<(), ConfigOverrideError> {
+        let invalid = |reason: String| ConfigOverrideError::InvalidValue {
+            key: key.to_string(),
+            value: value.to_string(),
+            reason,
+        };
+
+        let mut root = serde_json::to_value(&*self).map_err(|error| invalid(error.to_string()))?;
+        let mut slot = &mut root;
+        for part in key.split('.') {
+            slot = slot
+                .get_mut(part)
+                .ok_or_else(|| ConfigOverrideError::UnknownKey(key.to_string()))?;
+        }
+        *slot = serde_json::from_str(value)
+            .unwrap_or_else(|_| serde_json::Value::String(value.to_string()));
+
+        *self = serde_json::from_value(root).map_err(|error| invalid(error.to_string()))?;
+        Ok(())
+    }
+
+    /// Applies every override in order
+    pub fn apply_overrides(
+        &mut self,
+        overrides: &[ConfigOverride],
+    ) -> Result<(), ConfigOverrideError> {
+        for config_override in overrides {
+            self.apply_override(&config_override.key, &config_override.value)?;
+        }
+        Ok(())
+    }
+
+    /// Checks for values that would break spawning, movement or the window
+    pub fn validate(&self) -> Result<(), ConfigValidationError> {
+        let positive = [
+            ("player.speed", self.player.speed),
+            ("player.max_health", self.player.max_health),
+            ("player.jump_velocity", self.player.jump_velocity),
+            (
+                "player.swim_stroke_velocity",
+                self.player.swim_stroke_velocity,
+            ),
+            ("player.breath", self.player.breath.unwrap_or(1.0)),
+            ("obstacles.spawn_interval", self.obstacles.spawn_interval),
+            ("obstacles.width_min", self.obstacles.width_min),
+            ("obstacles.height_min", self.obstacles.height_min),
+            ("obstacles.despawn_margin", self.obstacles.despawn_margin),
+            ("camera.target_framerate", self.camera.target_framerate),
+            ("window.width", self.window.width),
+            ("window.height", self.window.height),
+        ];
+        for (entry, value) in positive {
+            if !(value.is_finite() && value > 0.0) {
+                return Err(ConfigValidationError {
+                    entry: entry.to_string(),
+                    reason: format!("must be positive, got {value}"),
+                });
+            }
+        }
+
+        let ranges = [
+            (
+                "obstacles.spawn_y",
+                self.obstacles.spawn_y_min,
+                self.obstacles.spawn_y_max,
+            ),
+            (
+                "obstacles.width",
+                self.obstacles.width_min,
+                self.obstacles.width_max,
+            ),
+            (
+                "obstacles.height",
+                self.obstacles.height_min,
+                self.obstacles.height_max,
+            ),
+            (
+                "obstacles.speed",
+                self.obstacles.speed_min,
+                self.obstacles.speed_max,
+            ),
+        ];
+        for (entry, min, max) in ranges {
+            // Random ranges must not be empty
+            if !(min.is_finite() && max.is_finite() && min < max) {
+                return Err(ConfigValidationError {
+                    entry: format!("{entry}_min"),
+                    reason: format!("must be below {entry}_max ({max}), got {min}"),
+                });
+            }
+        }
+
+        if self.obstacles.spawn_edges.is_empty() {
+            return Err(ConfigValidationError {
+                entry: "obstacles.spawn_edges".to_string(),
+                reason: "must list at least one edge".to_string(),
+            });
+        }
+        // Obstacles spawned outside the despawn area would be removed at once
+        if !(0.0..self.obstacles.despawn_margin).contains(&self.obstacles.spawn_margin) {
+            return Err(ConfigValidationError {
+                entry: "obstacles.spawn_margin".to_string(),
+                reason: format!(
+                    "must be between 0 and obstacles.despawn_margin ({}), got {}",
+                    self.obstacles.despawn_margin, self.obstacles.spawn_margin
+                ),
+            });
+        }
+
+        if !(0.0..=1.0).contains(&self.player.jump_cut_multiplier) {
+            return Err(ConfigValidationError {
+                entry: "player.jump_cut_multiplier".to_string(),
+                reason: format!(
+                    "must be between 0 and 1, got {}",
+                    self.player.jump_cut_multiplier
+                ),
+            });
+        }
+        if !(self.player.drowning_damage.is_finite() && self.player.drowning_damage >= 0.0) {
+            return Err(ConfigValidationError {
+                entry: "player.drowning_damage".to_string(),
+                reason: format!("must not be negative, got {}", self.player.drowning_damage),
+            });
+        }
+        for (entry, damage) in [
+            ("out_of_bounds_damage", self.player.out_of_bounds_damage),
+            ("crush_damage", self.player.crush_damage),
+        ] {
+            if let Some(damage) = damage.filter(|damage| !(damage.is_finite() && *damage >= 0.0)) {
+                return Err(ConfigValidationError {
+                    entry: format!("player.{entry}"),
+                    reason: format!("must not be negative, got {damage}"),
+                });
+            }
+        }
+        if !(0.0..90.0).contains(&self.player.max_slope_angle) {
+            return Err(ConfigValidationError {
+                entry: "player.max_slope_angle".to_string(),
+                reason: format!(
+                    "must be between 0 and 90 degrees, got {}",
+                    self.player.max_slope_angle
+                ),
+            });
+        }
+        let smoothing = CameraFollow::MIN_SMOOTHING..=CameraFollow::MAX_SMOOTHING;
+        if !smoothing.contains(&self.camera.smoothing) {
+            return Err(ConfigValidationError {
+                entry: "camera.smoothing".to_string(),
+                reason: format!(
+                    "must be between {} and {}, got {}",
+                    smoothing.start(),
+                    smoothing.end(),
+                    self.camera.smoothing
+                ),
+            });
+        }
+
+        self.difficulty.validate()?;
+        self.waves.validate()
+    }
+}
+
+/// Errors produced while loading a config file
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum ConfigLoadError {
+    /// The file could not be read
+    #[error("could not read config file: {0}")]
+    Io(#[from] std::io::Error),
+    /// The file is not valid UTF-8
+    #[error("config file is not valid UTF-8: {0}")]
+    Utf8(#[from] std::str::Utf8Error),
+    /// The RON syntax or structure is invalid
+    #[error("could not parse config: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    /// A command line override could not be applied
+    #[error("could not apply override: {0}")]
+    Override(#[from] ConfigOverrideError),
+    /// The config parsed but contains invalid values
+    #[error("invalid config: {0}")]
+    Invalid(#[from] ConfigValidationError),
+}
+
+/// Loads [`GameConfig`] from `.config.ron` files
+#[derive(Default)]
+pub struct GameConfigLoader;
+
+impl AssetLoader for GameConfigLoader {
+    type Asset = GameConfig;
+    type Settings = ();
+    type Error = ConfigLoadError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let config = GameConfig::from_ron(std::str::from_utf8(&bytes)?)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["config.ron"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::difficulty::DifficultyPreset;
+
+    #[test]
+    fn test_partial_config_keeps_defaults() {
+        let config = GameConfig::from_ron("(player: (jump_velocity: 600.0))").unwrap();
+        assert_eq!(config.player.jump_velocity, 600.0);
+        assert_eq!(config.player.gravity, 980.0);
+        assert_eq!(config.obstacles.spawn_edges, vec![ScreenEdge::Right]);
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_difficulty_section_parses_curves() {
+        let config = GameConfig::from_ron(
+            "(difficulty: (
+                preset: Easy,
+                speed: Some(Linear(from: 1.0, to: 2.5, duration: 240.0)),
+                size: Some(Stepped(from: 1.0, step: 0.1, every: 30.0, limit: 1.5)),
+            ))",
+        )
+        .unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.difficulty.preset, DifficultyPreset::Easy);
+        assert_eq!(config.difficulty.sample(120.0).speed, 1.75);
+    }
+
+    #[test]
+    fn test_collision_section_sets_layer_filters() {
+        let config =
+            GameConfig::from_ron("(collision: (enemy: [Player, Enemy, Platform]))").unwrap();
+        let enemy = config.collision.layers(CollisionLayer::Enemy);
+        assert!(enemy.interacts_with(&enemy));
+        assert!(enemy.interacts_with(&config.collision.layers(CollisionLayer::Player)));
+        assert!(!enemy.interacts_with(&config.collision.layers(CollisionLayer::Pickup)));
+
+        let both = config.collision.layers_for(CollisionLayer::mask(&[
+            CollisionLayer::Pickup,
+            CollisionLayer::Goal,
+        ]));
+        assert_eq!(
+            both.filter,
+            CollisionLayer::mask(&[CollisionLayer::Player, CollisionLayer::Platform])
+        );
+    }
+
+    #[test]
+    fn test_validate_rejects_empty_range() {
+        let mut config = GameConfig::default();
+        config.obstacles.speed_min = 300.0;
+
+        let error = config.validate().unwrap_err();
+        assert_eq!(error.entry, "obstacles.speed_min");
+    }
+
+    #[test]
+    fn test_apply_override() {
+        let mut config = GameConfig::default();
+        config
+            .apply_override("obstacles.spawn_interval", "0.5")
+            .unwrap();
+        config.apply_override("camera.offset", "[0, 80]").unwrap();
+        config.apply_override("window.title", "Speedrun").unwrap();
+        config.apply_override("seed", "42").unwrap();
+        config.apply_override("difficulty.preset", "Hard").unwrap();
+
+        assert_eq!(config.obstacles.spawn_interval, 0.5);
+        assert_eq!(config.camera.offset, Vec2::new(0.0, 80.0));
+        assert_eq!(config.window.title, "Speedrun");
+        assert_eq!(config.seed, Some(42));
+        assert_eq!(config.difficulty.preset, DifficultyPreset::Hard);
+    }
+
+    #[test]
+    fn test_apply_override_rejects_bad_input() {
+        let mut config = GameConfig::default();
+
+        assert_eq!(
+            config.apply_override("obstacles.spawn_rate", "1"),
+            Err(ConfigOverrideError::UnknownKey(
+                "obstacles.spawn_rate".to_string()
+            ))
+        );
+        assert!(matches!(
+            config.apply_override("scoring.enemy_stomp_points", "lots"),
+            Err(ConfigOverrideError::InvalidValue { .. })
+        ));
+        assert!(matches!(
+            "scoring.enemy_stomp_points".parse::<ConfigOverride>(),
+            Err(ConfigOverrideError::MissingValue(_))
+        ));
+    }
+}
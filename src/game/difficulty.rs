@@ -0,0 +1,304 @@
+//! Difficulty curves and presets
+//!
+//! The difficulty director samples a set of curves at the current survival
+//! time ([`GameTimer::elapsed`](crate::resources::GameTimer)) to get the
+//! [`Difficulty`] multipliers applied to obstacle spawning. Each curve can be
+//! authored in the config, and any curve left out comes from the selected
+//! [`DifficultyPreset`].
+
+use std::cmp::Ordering;
+
+use serde::{Deserialize, Serialize};
+
+use super::config::ConfigValidationError;
+use crate::resources::Difficulty;
+
+/// A value that changes with survival time, in seconds
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum DifficultyCurve {
+    /// Moves from `from` to `to` over `duration` seconds, then holds
+    Linear { from: f32, to: f32, duration: f32 },
+    /// Starts at `from` and changes by `step` every `every` seconds,
+    /// stopping at `limit`
+    Stepped {
+        from: f32,
+        step: f32,
+        every: f32,
+        limit: f32,
+    },
+    /// `(time, value)` keyframes with linear interpolation between them,
+    /// holding the first and last values outside their range
+    Keyframes(Vec<(f32, f32)>),
+}
+
+impl DifficultyCurve {
+    /// Returns the curve's value after `elapsed` seconds
+    pub fn sample(&self, elapsed: f32) -> f32 {
+        let elapsed = elapsed.max(0.0);
+        match self {
+            Self::Linear { from, to, duration } => {
+                let t = (elapsed / duration).min(1.0);
+                from + (to - from) * t
+            }
+            Self::Stepped {
+                from,
+                step,
+                every,
+                limit,
+            } => {
+                let value = from + step * (elapsed / every).floor();
+                value.clamp(from.min(*limit), from.max(*limit))
+            }
+            Self::Keyframes(keys) => {
+                let Some(next) = keys.iter().position(|&(time, _)| time > elapsed) else {
+                    return keys.last().map_or(1.0, |&(_, value)| value);
+                };
+                if next == 0 {
+                    return keys[0].1;
+                }
+                let (start_time, start) = keys[next - 1];
+                let (end_time, end) = keys[next];
+                start + (end - start) * (elapsed - start_time) / (end_time - start_time)
+            }
+        }
+    }
+
+    /// Checks that the curve is well formed and never drops to zero
+    fn validate(&self, entry: &str) -> Result<(), ConfigValidationError> {
+        let invalid = |reason: String| ConfigValidationError {
+            entry: entry.to_string(),
+            reason,
+        };
+
+        let (period, values) = match self {
+            Self::Linear { from, to, duration } => (*duration, vec![*from, *to]),
+            Self::Stepped {
+                from, every, limit, ..
+            } => (*every, vec![*from, *limit]),
+            Self::Keyframes(keys) => {
+                if keys.is_empty() {
+                    return Err(invalid("needs at least one keyframe".to_string()));
+                }
+                if keys
+                    .windows(2)
+                    .any(|pair| pair[0].0.partial_cmp(&pair[1].0) != Some(Ordering::Less))
+                {
+                    return Err(invalid("keyframe times must increase".to_string()));
+                }
+                (1.0, keys.iter().map(|&(_, value)| value).collect())
+            }
+        };
+
+        if !(period.is_finite() && period > 0.0) {
+            return Err(invalid(format!("duration must be positive, got {period}")));
+        }
+        if let Some(value) = values.iter().find(|v| !(v.is_finite() && **v > 0.0)) {
+            return Err(invalid(format!("values must be positive, got {value}")));
+        }
+        Ok(())
+    }
+}
+
+/// Built-in sets of difficulty curves
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DifficultyPreset {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl DifficultyPreset {
+    /// Curves for spawn rate, speed, size and damage
+    pub fn curves(self) -> DifficultyCurves {
+        match self {
+            Self::Easy => DifficultyCurves {
+                spawn_rate: DifficultyCurve::Linear {
+                    from: 0.75,
+                    to: 1.5,
+                    duration: 300.0,
+                },
+                speed: DifficultyCurve::Linear {
+                    from: 0.8,
+                    to: 1.3,
+                    duration: 300.0,
+                },
+                size: DifficultyCurve::Stepped {
+                    from: 1.0,
+                    step: 0.05,
+                    every: 60.0,
+                    limit: 1.25,
+                },
+                damage: DifficultyCurve::Keyframes(vec![(0.0, 0.5), (180.0, 1.0)]),
+            },
+            Self::Normal => DifficultyCurves {
+                spawn_rate: DifficultyCurve::Linear {
+                    from: 1.0,
+                    to: 2.0,
+                    duration: 180.0,
+                },
+                speed: DifficultyCurve::Linear {
+                    from: 1.0,
+                    to: 1.6,
+                    duration: 180.0,
+                },
+                size: DifficultyCurve::Stepped {
+                    from: 1.0,
+                    step: 0.1,
+                    every: 30.0,
+                    limit: 1.5,
+                },
+                damage: DifficultyCurve::Keyframes(vec![(60.0, 1.0), (120.0, 1.5), (240.0, 2.0)]),
+            },
+            Self::Hard => DifficultyCurves {
+                spawn_rate: DifficultyCurve::Linear {
+                    from: 1.25,
+                    to: 3.0,
+                    duration: 120.0,
+                },
+                speed: DifficultyCurve::Linear {
+                    from: 1.2,
+                    to: 2.0,
+                    duration: 120.0,
+                },
+                size: DifficultyCurve::Stepped {
+                    from: 1.1,
+                    step: 0.1,
+                    every: 20.0,
+                    limit: 1.8,
+                },
+                damage: DifficultyCurve::Keyframes(vec![(0.0, 1.5), (60.0, 2.0), (180.0, 3.0)]),
+            },
+        }
+    }
+}
+
+/// One curve per scaled obstacle property
+#[derive(Debug, Clone, PartialEq)]
+pub struct DifficultyCurves {
+    /// Multiplies the spawn rate, so `2.0` halves the spawn interval
+    pub spawn_rate: DifficultyCurve,
+    pub speed: DifficultyCurve,
+    pub size: DifficultyCurve,
+    pub damage: DifficultyCurve,
+}
+
+/// Difficulty settings: a preset plus optional custom curves
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DifficultyConfig {
+    pub preset: DifficultyPreset,
+    /// Replaces the preset's spawn rate curve
+    pub spawn_rate: Option<DifficultyCurve>,
+    /// Replaces the preset's speed curve
+    pub speed: Option<DifficultyCurve>,
+    /// Replaces the preset's size curve
+    pub size: Option<DifficultyCurve>,
+    /// Replaces the preset's damage curve
+    pub damage: Option<DifficultyCurve>,
+}
+
+impl DifficultyConfig {
+    /// The preset's curves with any custom curves applied
+    pub fn curves(&self) -> DifficultyCurves {
+        let preset = self.preset.curves();
+        DifficultyCurves {
+            spawn_rate: self.spawn_rate.clone().unwrap_or(preset.spawn_rate),
+            speed: self.speed.clone().unwrap_or(preset.speed),
+            size: self.size.clone().unwrap_or(preset.size),
+            damage: self.damage.clone().unwrap_or(preset.damage),
+        }
+    }
+
+    /// Samples every curve after `elapsed` seconds of survival
+    pub fn sample(&self, elapsed: f32) -> Difficulty {
+        let curves = self.curves();
+        Difficulty {
+            spawn_rate: curves.spawn_rate.sample(elapsed),
+            speed: curves.speed.sample(elapsed),
+            size: curves.size.sample(elapsed),
+            damage: curves.damage.sample(elapsed),
+        }
+    }
+
+    pub(super) fn validate(&self) -> Result<(), ConfigValidationError> {
+        let curves = self.curves();
+        curves.spawn_rate.validate("difficulty.spawn_rate")?;
+        curves.speed.validate("difficulty.speed")?;
+        curves.size.validate("difficulty.size")?;
+        curves.damage.validate("difficulty.damage")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_curves_sample() {
+        let linear = DifficultyCurve::Linear {
+            from: 1.0,
+            to: 2.0,
+            duration: 100.0,
+        };
+        assert_eq!(linear.sample(0.0), 1.0);
+        assert_eq!(linear.sample(50.0), 1.5);
+        assert_eq!(linear.sample(500.0), 2.0);
+
+        let stepped = DifficultyCurve::Stepped {
+            from: 1.0,
+            step: 0.25,
+            every: 10.0,
+            limit: 1.5,
+        };
+        assert_eq!(stepped.sample(9.9), 1.0);
+        assert_eq!(stepped.sample(10.0), 1.25);
+        assert_eq!(stepped.sample(60.0), 1.5);
+
+        let keyframes = DifficultyCurve::Keyframes(vec![(10.0, 1.0), (20.0, 3.0), (40.0, 2.0)]);
+        assert_eq!(keyframes.sample(0.0), 1.0);
+        assert_eq!(keyframes.sample(15.0), 2.0);
+        assert_eq!(keyframes.sample(30.0), 2.5);
+        assert_eq!(keyframes.sample(100.0), 2.0);
+    }
+
+    #[test]
+    fn test_presets_ramp_up() {
+        for preset in [
+            DifficultyPreset::Easy,
+            DifficultyPreset::Normal,
+            DifficultyPreset::Hard,
+        ] {
+            let config = DifficultyConfig {
+                preset,
+                ..Default::default()
+            };
+            assert!(config.validate().is_ok());
+
+            let (start, late) = (config.sample(0.0), config.sample(600.0));
+            assert!(late.spawn_rate > start.spawn_rate);
+            assert!(late.speed > start.speed);
+            assert!(late.size > start.size);
+            assert!(late.damage > start.damage);
+        }
+
+        let easy = DifficultyPreset::Easy.curves().speed.sample(60.0);
+        let hard = DifficultyPreset::Hard.curves().speed.sample(60.0);
+        assert!(easy < hard);
+    }
+
+    #[test]
+    fn test_custom_curve_replaces_preset() {
+        let config = DifficultyConfig {
+            speed: Some(DifficultyCurve::Keyframes(vec![(0.0, 3.0)])),
+            ..Default::default()
+        };
+        assert_eq!(config.sample(100.0).speed, 3.0);
+
+        let config = DifficultyConfig {
+            damage: Some(DifficultyCurve::Keyframes(vec![(10.0, 1.0), (5.0, 2.0)])),
+            ..Default::default()
+        };
+        assert_eq!(config.validate().unwrap_err().entry, "difficulty.damage");
+    }
+}
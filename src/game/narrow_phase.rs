@@ -0,0 +1,324 @@
+//! Narrow-phase contact tests between collider shapes
+//!
+//! Round shapes (circles and capsules) are treated as a segment swept by a
+//! radius, and boxes (axis-aligned or oriented) as a center, half size and
+//! angle. Every pair of shapes reduces to one of three tests: round–round
+//! via the closest points of the two segments, round–box via the point of
+//! the segment closest to the box, and box–box via separating axes.
+
+use bevy::prelude::*;
+
+use crate::components::Collider;
+
+/// How two overlapping shapes touch
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Contact {
+    /// Unit vector pointing from the second shape toward the first
+    pub normal: Vec2,
+    /// Distance the first shape must move along `normal` to separate them
+    pub depth: f32,
+}
+
+impl Contact {
+    /// The same contact seen from the other shape
+    pub fn flipped(self) -> Self {
+        Self {
+            normal: -self.normal,
+            depth: self.depth,
+        }
+    }
+}
+
+/// Rounds of closest-point refinement between a capsule's segment and a box
+const SEGMENT_BOX_ITERATIONS: usize = 3;
+
+/// A segment swept by a radius
+struct Round {
+    start: Vec2,
+    end: Vec2,
+    radius: f32,
+}
+
+/// A box rotated by `angle` around its center
+struct Box2 {
+    center: Vec2,
+    half_size: Vec2,
+    angle: f32,
+}
+
+enum Primitive {
+    Round(Round),
+    Box(Box2),
+}
+
+fn primitive(collider: &Collider, position: Vec2) -> Primitive {
+    match *collider {
+        Collider::Aabb { half_size } => Primitive::Box(Box2 {
+            center: position,
+            half_size,
+            angle: 0.0,
+        }),
+        Collider::Obb { half_size, angle } => Primitive::Box(Box2 {
+            center: position,
+            half_size,
+            angle,
+        }),
+        Collider::Circle { radius } => Primitive::Round(Round {
+            start: position,
+            end: position,
+            radius,
+        }),
+        Collider::Capsule {
+            radius,
+            half_height,
+        } => Primitive::Round(Round {
+            start: position - Vec2::Y * half_height,
+            end: position + Vec2::Y * half_height,
+            radius,
+        }),
+    }
+}
+
+/// Contact between `a` at `a_position` and `b` at `b_position`, or `None`
+/// when they do not overlap. Shapes that only touch do not overlap.
+pub fn contact(a: &Collider, a_position: Vec2, b: &Collider, b_position: Vec2) -> Option<Contact> {
+    match (primitive(a, a_position), primitive(b, b_position)) {
+        (Primitive::Round(a), Primitive::Round(b)) => round_round(&a, &b),
+        (Primitive::Round(a), Primitive::Box(b)) => round_box(&a, &b),
+        (Primitive::Box(a), Primitive::Round(b)) => round_box(&b, &a).map(Contact::flipped),
+        (Primitive::Box(a), Primitive::Box(b)) => box_box(&a, &b),
+    }
+}
+
+fn round_round(a: &Round, b: &Round) -> Option<Contact> {
+    let (on_a, on_b) = closest_points_between_segments(a.start, a.end, b.start, b.end);
+    circle_circle(on_a, a.radius, on_b, b.radius)
+}
+
+fn circle_circle(a: Vec2, a_radius: f32, b: Vec2, b_radius: f32) -> Option<Contact> {
+    let offset = a - b;
+    let distance = offset.length();
+    let depth = a_radius + b_radius - distance;
+    if depth <= 0.0 {
+        return None;
+    }
+    // Concentric circles have no preferred direction, push up
+    let normal = offset.try_normalize().unwrap_or(Vec2::Y);
+    Some(Contact { normal, depth })
+}
+
+fn round_box(round: &Round, bx: &Box2) -> Option<Contact> {
+    // Walk toward the point of the segment nearest the box; exact for
+    // circles and converges quickly for short capsules
+    let mut point = closest_point_on_segment(round.start, round.end, bx.center);
+    for _ in 0..SEGMENT_BOX_ITERATIONS {
+        let on_box = bx.closest_point(point);
+        point = closest_point_on_segment(round.start, round.end, on_box);
+    }
+    circle_box(point, round.radius, bx)
+}
+
+fn circle_box(center: Vec2, radius: f32, bx: &Box2) -> Option<Contact> {
+    let local = bx.to_local(center);
+    let clamped = local.clamp(-bx.half_size, bx.half_size);
+
+    if local != clamped {
+        // Center outside the box: push away from the nearest point on it
+        let offset = local - clamped;
+        let distance = offset.length();
+        let depth = radius - distance;
+        if depth <= 0.0 {
+            return None;
+        }
+        return Some(Contact {
+            normal: bx.to_world_direction(offset / distance),
+            depth,
+        });
+    }
+
+    // Center inside the box: push out through the nearest face
+    let inside = bx.half_size - local.abs();
+    let normal = if inside.x < inside.y {
+        Vec2::new(sign(local.x), 0.0)
+    } else {
+        Vec2::new(0.0, sign(local.y))
+    };
+    Some(Contact {
+        normal: bx.to_world_direction(normal),
+        depth: inside.min_element() + radius,
+    })
+}
+
+fn box_box(a: &Box2, b: &Box2) -> Option<Contact> {
+    let offset = a.center - b.center;
+    let mut best: Option<Contact> = None;
+
+    for axis in a.axes().into_iter().chain(b.axes()) {
+        let overlap = a.extent_along(axis) + b.extent_along(axis) - offset.dot(axis).abs();
+        if overlap <= 0.0 {
+            return None;
+        }
+        if best.is_none_or(|best| overlap < best.depth) {
+            let normal = if offset.dot(axis) < 0.0 { -axis } else { axis };
+            best = Some(Contact {
+                normal,
+                depth: overlap,
+            });
+        }
+    }
+    best
+}
+
+impl Box2 {
+    fn to_local(&self, point: Vec2) -> Vec2 {
+        Vec2::from_angle(-self.angle).rotate(point - self.center)
+    }
+
+    fn to_world_direction(&self, direction: Vec2) -> Vec2 {
+        Vec2::from_angle(self.angle).rotate(direction)
+    }
+
+    fn closest_point(&self, point: Vec2) -> Vec2 {
+        let local = self.to_local(point).clamp(-self.half_size, self.half_size);
+        self.center + self.to_world_direction(local)
+    }
+
+    fn axes(&self) -> [Vec2; 2] {
+        let x = Vec2::from_angle(self.angle);
+        [x, x.perp()]
+    }
+
+    /// Half the length of the box's shadow on `axis`
+    fn extent_along(&self, axis: Vec2) -> f32 {
+        let [x, y] = self.axes();
+        self.half_size.x * x.dot(axis).abs() + self.half_size.y * y.dot(axis).abs()
+    }
+}
+
+fn sign(value: f32) -> f32 {
+    if value < 0.0 {
+        -1.0
+    } else {
+        1.0
+    }
+}
+
+fn closest_point_on_segment(start: Vec2, end: Vec2, point: Vec2) -> Vec2 {
+    let segment = end - start;
+    let length_squared = segment.length_squared();
+    if length_squared == 0.0 {
+        return start;
+    }
+    let t = ((point - start).dot(segment) / length_squared).clamp(0.0, 1.0);
+    start + segment * t
+}
+
+/// Closest points on segments `a` and `b` to each other
+fn closest_points_between_segments(
+    a_start: Vec2,
+    a_end: Vec2,
+    b_start: Vec2,
+    b_end: Vec2,
+) -> (Vec2, Vec2) {
+    // Crossing segments touch where they cross
+    let (a, b) = (a_end - a_start, b_end - b_start);
+    let denominator = a.perp_dot(b);
+    if denominator != 0.0 {
+        let t = (b_start - a_start).perp_dot(b) / denominator;
+        let u = (b_start - a_start).perp_dot(a) / denominator;
+        if (0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u) {
+            let point = a_start + a * t;
+            return (point, point);
+        }
+    }
+
+    // Otherwise the closest pair includes an end point of one segment
+    [
+        (a_start, closest_point_on_segment(b_start, b_end, a_start)),
+        (a_end, closest_point_on_segment(b_start, b_end, a_end)),
+        (closest_point_on_segment(a_start, a_end, b_start), b_start),
+        (closest_point_on_segment(a_start, a_end, b_end), b_end),
+    ]
+    .into_iter()
+    .min_by(|(p, q), (r, s)| p.distance_squared(*q).total_cmp(&r.distance_squared(*s)))
+    .unwrap_or((a_start, b_start))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_contact(contact: Option<Contact>, normal: Vec2, depth: f32) {
+        let contact = contact.expect("shapes should overlap");
+        assert!(
+            contact.normal.abs_diff_eq(normal, 1e-4),
+            "normal {:?}",
+            contact.normal
+        );
+        assert!(
+            (contact.depth - depth).abs() < 1e-4,
+            "depth {}",
+            contact.depth
+        );
+    }
+
+    #[test]
+    fn test_circle_circle() {
+        let circle = Collider::circle(10.0);
+        assert_contact(
+            contact(&circle, Vec2::new(15.0, 0.0), &circle, Vec2::ZERO),
+            Vec2::X,
+            5.0,
+        );
+        assert!(contact(&circle, Vec2::new(20.0, 0.0), &circle, Vec2::ZERO).is_none());
+    }
+
+    #[test]
+    fn test_box_circle() {
+        let ground = Collider::aabb(100.0, 20.0);
+        let ball = Collider::circle(10.0);
+
+        // Resting on top, sunk in by 2
+        assert_contact(
+            contact(&ball, Vec2::new(0.0, 18.0), &ground, Vec2::ZERO),
+            Vec2::Y,
+            2.0,
+        );
+        // Seen from the box the normal flips
+        assert_contact(
+            contact(&ground, Vec2::ZERO, &ball, Vec2::new(0.0, 18.0)),
+            Vec2::NEG_Y,
+            2.0,
+        );
+        // Hanging over the corner pushes out diagonally
+        let corner = Vec2::new(50.0, 10.0);
+        let center = corner + Vec2::splat(5.0);
+        assert_contact(
+            contact(&ball, center, &ground, Vec2::ZERO),
+            Vec2::ONE.normalize(),
+            10.0 - 5.0 * 2f32.sqrt(),
+        );
+    }
+
+    #[test]
+    fn test_capsule_and_oriented_box() {
+        let capsule = Collider::capsule(10.0, 15.0);
+        let ground = Collider::aabb(100.0, 20.0);
+        // The bottom cap reaches 25 below the center
+        assert_contact(
+            contact(&capsule, Vec2::new(0.0, 30.0), &ground, Vec2::ZERO),
+            Vec2::Y,
+            5.0,
+        );
+
+        // A box rotated 45° is a diamond reaching 10 * sqrt(2) along x
+        let diamond = Collider::obb(20.0, 20.0, std::f32::consts::FRAC_PI_4);
+        let square = Collider::aabb(20.0, 20.0);
+        assert_contact(
+            contact(&square, Vec2::new(20.0, 0.0), &diamond, Vec2::ZERO),
+            Vec2::X,
+            10.0 * 2f32.sqrt() - 10.0,
+        );
+        assert!(contact(&square, Vec2::new(25.0, 0.0), &diamond, Vec2::ZERO).is_none());
+    }
+}
@@ -0,0 +1,381 @@
+//! Obstacle waves authored in the config
+//!
+//! A wave is a timed list of spawn patterns: random single obstacles or
+//! formations such as walls with a gap and staircases built from named
+//! obstacle types. Waves play back in order or are picked at random by
+//! weight. With no waves configured, obstacles spawn one at a time on the
+//! spawn timer.
+
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::config::ConfigValidationError;
+use crate::components::ShapeKind;
+
+/// Wave playback settings
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WaveConfig {
+    pub order: WaveOrder,
+    /// Obstacle types referenced by name from patterns
+    pub obstacle_types: BTreeMap<String, ObstacleType>,
+    pub waves: Vec<WaveData>,
+}
+
+/// How the next wave is chosen
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WaveOrder {
+    /// Plays the waves in the order they are listed, then repeats
+    #[default]
+    InOrder,
+    /// Picks each wave at random, weighted by [`WaveData::weight`]
+    WeightedRandom,
+}
+
+/// A named kind of obstacle used in formations
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ObstacleType {
+    pub size: Vec2,
+    pub speed: f32,
+    /// Contact damage, defaults to `obstacles.damage`
+    #[serde(default)]
+    pub damage: Option<f32>,
+    #[serde(default)]
+    pub color: Option<[f32; 3]>,
+    #[serde(default)]
+    pub movement: ObstacleMovement,
+    /// Collision shape fitted to `size`
+    #[serde(default)]
+    pub shape: ShapeKind,
+}
+
+/// How an obstacle moves after spawning
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub enum ObstacleMovement {
+    /// Straight to the left at the type's speed
+    #[default]
+    Straight,
+    /// Straight, bobbing up and down along a sine wave
+    Sine { amplitude: f32, frequency: f32 },
+    /// Steers toward the player, turning at most `turn_rate` radians per second
+    Homing { turn_rate: f32 },
+    /// Falls under gravity and bounces off platforms, keeping `restitution`
+    /// of its speed on each bounce
+    Bounce {
+        gravity: f32,
+        restitution: f32,
+        #[serde(default)]
+        launch_speed: f32,
+    },
+    /// Circles a point that travels to the left
+    Orbit { radius: f32, angular_speed: f32 },
+    /// Follows a spline through `points`, relative to the spawn position, at
+    /// the type's speed
+    Spline {
+        points: Vec<Vec2>,
+        #[serde(default)]
+        looping: bool,
+    },
+}
+
+/// A timed sequence of spawn patterns
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WaveData {
+    pub name: String,
+    /// Relative chance of being picked with [`WaveOrder::WeightedRandom`]
+    #[serde(default = "default_weight")]
+    pub weight: f32,
+    /// Seconds of quiet after the last entry before the next wave starts
+    #[serde(default)]
+    pub rest: f32,
+    pub entries: Vec<WaveEntry>,
+}
+
+fn default_weight() -> f32 {
+    1.0
+}
+
+/// A pattern spawned `delay` seconds after the previous entry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WaveEntry {
+    #[serde(default)]
+    pub delay: f32,
+    pub pattern: SpawnPattern,
+}
+
+/// What a wave entry spawns
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SpawnPattern {
+    /// A single obstacle randomised from the `obstacles` settings
+    Random,
+    /// A single obstacle at height `y`
+    Single { kind: String, y: f32 },
+    /// A column of obstacles from `bottom` to `top`, leaving a gap of
+    /// `gap_height` centred on `gap_y`
+    Wall {
+        kind: String,
+        bottom: f32,
+        top: f32,
+        gap_y: f32,
+        gap_height: f32,
+    },
+    /// `steps` obstacles, each `spacing` further right and `rise` higher
+    /// than the last
+    Staircase {
+        kind: String,
+        y: f32,
+        steps: u32,
+        rise: f32,
+        spacing: f32,
+    },
+}
+
+/// An obstacle placed by a pattern
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Placement<'a> {
+    /// Offset along x from the spawn line and absolute y
+    pub position: Vec2,
+    pub kind: &'a ObstacleType,
+}
+
+impl WaveConfig {
+    /// Obstacles placed by `pattern`. [`SpawnPattern::Random`] places none,
+    /// the spawner randomises it.
+    pub fn placements(&self, pattern: &SpawnPattern) -> Vec<Placement<'_>> {
+        match pattern {
+            SpawnPattern::Random => Vec::new(),
+            SpawnPattern::Single { kind, y } => self
+                .obstacle_type(kind)
+                .map(|kind| Placement {
+                    position: Vec2::new(0.0, *y),
+                    kind,
+                })
+                .into_iter()
+                .collect(),
+            SpawnPattern::Wall {
+                kind,
+                bottom,
+                top,
+                gap_y,
+                gap_height,
+            } => {
+                let Some(kind) = self.obstacle_type(kind) else {
+                    return Vec::new();
+                };
+                let height = kind.size.y;
+                let gap = (gap_y - gap_height / 2.0)..(gap_y + gap_height / 2.0);
+                let count = ((top - bottom) / height).floor().max(0.0) as u32;
+
+                (0..count)
+                    .map(|i| bottom + (i as f32 + 0.5) * height)
+                    .filter(|y| {
+                        // Keep blocks that do not intrude into the gap
+                        y + height / 2.0 <= gap.start || y - height / 2.0 >= gap.end
+                    })
+                    .map(|y| Placement {
+                        position: Vec2::new(0.0, y),
+                        kind,
+                    })
+                    .collect()
+            }
+            SpawnPattern::Staircase {
+                kind,
+                y,
+                steps,
+                rise,
+                spacing,
+            } => {
+                let Some(kind) = self.obstacle_type(kind) else {
+                    return Vec::new();
+                };
+                (0..*steps)
+                    .map(|i| Placement {
+                        position: Vec2::new(i as f32 * spacing, y + i as f32 * rise),
+                        kind,
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    fn obstacle_type(&self, name: &str) -> Option<&ObstacleType> {
+        self.obstacle_types.get(name)
+    }
+
+    /// Checks timings, weights and obstacle type references
+    pub(super) fn validate(&self) -> Result<(), ConfigValidationError> {
+        let invalid = |entry: String, reason: String| ConfigValidationError { entry, reason };
+
+        for (name, kind) in &self.obstacle_types {
+            let entry = format!("waves.obstacle_types.{name}");
+            if !(kind.size.min_element() > 0.0 && kind.speed.is_finite()) {
+                return Err(invalid(
+                    entry,
+                    "size must be positive and speed finite".to_string(),
+                ));
+            }
+            match &kind.movement {
+                ObstacleMovement::Bounce { restitution, .. }
+                    if !(0.0..=1.0).contains(restitution) =>
+                {
+                    return Err(invalid(
+                        format!("{entry}.movement"),
+                        format!("restitution must be between 0 and 1, got {restitution}"),
+                    ));
+                }
+                ObstacleMovement::Spline { points, .. } if points.len() < 2 => {
+                    return Err(invalid(
+                        format!("{entry}.movement"),
+                        "a spline needs at least two points".to_string(),
+                    ));
+                }
+                _ => {}
+            }
+        }
+
+        for (i, wave) in self.waves.iter().enumerate() {
+            let entry = format!("waves.waves[{i}]");
+            if wave.entries.is_empty() {
+                return Err(invalid(entry, "has no entries".to_string()));
+            }
+            if !(wave.weight.is_finite() && wave.weight > 0.0) {
+                return Err(invalid(
+                    format!("{entry}.weight"),
+                    format!("must be positive, got {}", wave.weight),
+                ));
+            }
+
+            let mut length = wave.rest;
+            for (j, wave_entry) in wave.entries.iter().enumerate() {
+                let entry = format!("{entry}.entries[{j}]");
+                if !(wave_entry.delay.is_finite() && wave_entry.delay >= 0.0) {
+                    return Err(invalid(
+                        format!("{entry}.delay"),
+                        format!("must not be negative, got {}", wave_entry.delay),
+                    ));
+                }
+                length += wave_entry.delay;
+
+                let kind = match &wave_entry.pattern {
+                    SpawnPattern::Random => None,
+                    SpawnPattern::Single { kind, .. }
+                    | SpawnPattern::Wall { kind, .. }
+                    | SpawnPattern::Staircase { kind, .. } => Some(kind),
+                };
+                if let Some(kind) = kind.filter(|kind| self.obstacle_type(kind).is_none()) {
+                    return Err(invalid(
+                        format!("{entry}.pattern"),
+                        format!("unknown obstacle type \"{kind}\""),
+                    ));
+                }
+            }
+
+            // A wave that takes no time would spawn forever in one frame
+            if !(wave.rest >= 0.0 && length > 0.0) {
+                return Err(invalid(
+                    format!("{entry}.rest"),
+                    "the wave's delays and rest must add up to a positive time".to_string(),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block_config() -> WaveConfig {
+        WaveConfig {
+            obstacle_types: BTreeMap::from([(
+                "block".to_string(),
+                ObstacleType {
+                    size: Vec2::splat(40.0),
+                    speed: 200.0,
+                    damage: None,
+                    color: None,
+                    movement: ObstacleMovement::Straight,
+                    shape: ShapeKind::Box,
+                },
+            )]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_wall_leaves_gap() {
+        let config = block_config();
+        let wall = SpawnPattern::Wall {
+            kind: "block".to_string(),
+            bottom: -200.0,
+            top: 200.0,
+            gap_y: 0.0,
+            gap_height: 80.0,
+        };
+
+        let heights: Vec<f32> = config
+            .placements(&wall)
+            .iter()
+            .map(|placement| placement.position.y)
+            .collect();
+        assert_eq!(
+            heights,
+            vec![-180.0, -140.0, -100.0, -60.0, 60.0, 100.0, 140.0, 180.0]
+        );
+    }
+
+    #[test]
+    fn test_staircase_rises_to_the_right() {
+        let config = block_config();
+        let stairs = SpawnPattern::Staircase {
+            kind: "block".to_string(),
+            y: -200.0,
+            steps: 3,
+            rise: 40.0,
+            spacing: 60.0,
+        };
+
+        let positions: Vec<Vec2> = config
+            .placements(&stairs)
+            .iter()
+            .map(|placement| placement.position)
+            .collect();
+        assert_eq!(
+            positions,
+            vec![
+                Vec2::new(0.0, -200.0),
+                Vec2::new(60.0, -160.0),
+                Vec2::new(120.0, -120.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_rejects_unknown_type_and_zero_length_wave() {
+        let mut config = block_config();
+        config.waves.push(WaveData {
+            name: "spike".to_string(),
+            weight: 1.0,
+            rest: 1.0,
+            entries: vec![WaveEntry {
+                delay: 0.0,
+                pattern: SpawnPattern::Single {
+                    kind: "spike".to_string(),
+                    y: 0.0,
+                },
+            }],
+        });
+        assert_eq!(
+            config.validate().unwrap_err().entry,
+            "waves.waves[0].entries[0].pattern"
+        );
+
+        config.waves[0].entries[0].pattern = SpawnPattern::Random;
+        config.waves[0].rest = 0.0;
+        assert_eq!(config.validate().unwrap_err().entry, "waves.waves[0].rest");
+    }
+}
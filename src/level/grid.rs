@@ -0,0 +1,156 @@
+//! Helpers for turning tile grids into merged platform rectangles
+
+use bevy::prelude::*;
+
+use super::PlatformData;
+
+/// A rectangle of grid cells, with `y = 0` being the top row
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GridRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl GridRect {
+    /// Converts the rectangle to a platform in world space.
+    ///
+    /// Grid rows grow downwards while world Y grows upwards, so the grid's
+    /// bottom edge (`rows * tile_size.y`) maps to `origin.y`.
+    pub fn to_platform(&self, tile_size: Vec2, rows: u32, origin: Vec2) -> PlatformData {
+        let size = Vec2::new(self.width as f32, self.height as f32) * tile_size;
+        let top_left = Vec2::new(
+            self.x as f32 * tile_size.x,
+            (rows - self.y) as f32 * tile_size.y,
+        );
+
+        PlatformData {
+            position: origin + top_left + Vec2::new(size.x, -size.y) / 2.0,
+            size,
+            color: None,
+            material: None,
+        }
+    }
+}
+
+/// Merges solid cells into as few rectangles as possible (greedy meshing).
+///
+/// Each rectangle grows right along its first row, then down for as long
+/// as every cell of the next row is solid and not yet covered.
+pub fn greedy_mesh(width: u32, height: u32, solid: impl Fn(u32, u32) -> bool) -> Vec<GridRect> {
+    let mut covered = vec![false; (width * height) as usize];
+    let free = |covered: &[bool], x: u32, y: u32| !covered[(y * width + x) as usize] && solid(x, y);
+    let mut rects = Vec::new();
+
+    for y in 0..height {
+        let mut x = 0;
+        while x < width {
+            if !free(&covered, x, y) {
+                x += 1;
+                continue;
+            }
+
+            let mut rect_width = 1;
+            while x + rect_width < width && free(&covered, x + rect_width, y) {
+                rect_width += 1;
+            }
+            let mut rect_height = 1;
+            while y + rect_height < height
+                && (x..x + rect_width).all(|column| free(&covered, column, y + rect_height))
+            {
+                rect_height += 1;
+            }
+
+            for row in y..y + rect_height {
+                for column in x..x + rect_width {
+                    covered[(row * width + column) as usize] = true;
+                }
+            }
+            rects.push(GridRect {
+                x,
+                y,
+                width: rect_width,
+                height: rect_height,
+            });
+            x += rect_width;
+        }
+    }
+
+    rects
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_greedy_mesh_merges_blocks() {
+        // Row 0: ##.#
+        // Row 1: ##.#
+        // Row 2: ####
+        let cells = [[1, 1, 0, 1], [1, 1, 0, 1], [1, 1, 1, 1]];
+        let rects = greedy_mesh(4, 3, |x, y| cells[y as usize][x as usize] == 1);
+
+        assert_eq!(
+            rects,
+            vec![
+                GridRect {
+                    x: 0,
+                    y: 0,
+                    width: 2,
+                    height: 3
+                },
+                GridRect {
+                    x: 3,
+                    y: 0,
+                    width: 1,
+                    height: 3
+                },
+                GridRect {
+                    x: 2,
+                    y: 2,
+                    width: 1,
+                    height: 1
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_greedy_mesh_covers_every_solid_cell_once() {
+        let cells = [
+            [1, 0, 1, 1, 0],
+            [1, 1, 1, 0, 0],
+            [0, 1, 1, 1, 1],
+            [1, 1, 0, 1, 1],
+        ];
+        let rects = greedy_mesh(5, 4, |x, y| cells[y as usize][x as usize] == 1);
+
+        let mut covered = [[0; 5]; 4];
+        for rect in &rects {
+            for y in rect.y..rect.y + rect.height {
+                for x in rect.x..rect.x + rect.width {
+                    covered[y as usize][x as usize] += 1;
+                }
+            }
+        }
+        assert_eq!(covered, cells);
+        // One rectangle per row would need 8
+        assert!(rects.len() < 8);
+    }
+
+    #[test]
+    fn test_grid_rect_to_platform_flips_y() {
+        let rect = GridRect {
+            x: 1,
+            y: 3,
+            width: 2,
+            height: 1,
+        };
+        let platform = rect.to_platform(Vec2::splat(16.0), 4, Vec2::ZERO);
+
+        assert_eq!(platform.size, Vec2::new(32.0, 16.0));
+        assert_eq!(platform.position, Vec2::new(32.0, 8.0));
+    }
+}
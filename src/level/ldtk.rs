@@ -0,0 +1,655 @@
+//! Importer for LDtk projects (`.ldtk`)
+//!
+//! Every LDtk level becomes a [`LevelData`] labeled asset named after the
+//! level identifier, so `world.ldtk#Level_1` loads a single level and
+//! `world.ldtk` loads the first one. IntGrid layers become merged platform
+//! rectangles; IntGrid values identified as `one_way` make one-way platforms
+//! and ones identified as `ladder` climbable areas. Entity instances spawn
+//! typed entities by identifier (`Player`, `Goal`, `Enemy`, `Coin`,
+//! `Platform`, `OneWayPlatform`, `Ramp`, `Ladder`, `Water`, `KillZone`, `Crate`,
+//! `PressurePlate`) with their fields
+//! mapped onto the matching level fields, compared case-insensitively and
+//! ignoring underscores (`PatrolDistance` and `patrol_distance` are the same
+//! field).
+//!
+//! Levels keep their LDtk world position (with Y flipped), so neighbouring
+//! levels line up and the player can walk from one into the next.
+
+use bevy::asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext};
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use serde::Deserialize;
+use thiserror::Error;
+
+use super::grid::greedy_mesh;
+use super::{
+    default_background_color, default_collectible_points, default_crate_mass, default_crate_size,
+    default_enemy_damage, default_enemy_size, default_enemy_speed, default_goal_size,
+    default_pressure_plate_size, default_time_limit, parse_hex_rgb, CollectibleData, CrateData,
+    EnemyData, GoalData, KillZoneData, LadderData, LevelData, LevelLoadError, PlatformData,
+    PressurePlateData, RampData, WaterData,
+};
+use crate::components::WaterVolume;
+
+/// An LDtk project as stored in a `.ldtk` file
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LdtkProject {
+    #[serde(default)]
+    pub bg_color: Option<String>,
+    #[serde(default)]
+    pub world_layout: Option<String>,
+    #[serde(default)]
+    pub defs: LdtkDefinitions,
+    #[serde(default)]
+    pub levels: Vec<LdtkLevel>,
+    /// Levels of multi-world projects
+    #[serde(default)]
+    pub worlds: Vec<LdtkWorld>,
+}
+
+/// A world of a multi-world LDtk project
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LdtkWorld {
+    #[serde(default)]
+    pub world_layout: Option<String>,
+    #[serde(default)]
+    pub levels: Vec<LdtkLevel>,
+}
+
+/// Project definitions needed to interpret layer data
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct LdtkDefinitions {
+    #[serde(default)]
+    pub layers: Vec<LdtkLayerDefinition>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LdtkLayerDefinition {
+    pub identifier: String,
+    #[serde(default)]
+    pub int_grid_values: Vec<LdtkIntGridValue>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LdtkIntGridValue {
+    pub value: i32,
+    #[serde(default)]
+    pub identifier: Option<String>,
+}
+
+/// A single LDtk level
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LdtkLevel {
+    pub identifier: String,
+    pub iid: String,
+    pub world_x: i32,
+    pub world_y: i32,
+    pub px_wid: u32,
+    pub px_hei: u32,
+    #[serde(default, rename = "__bgColor")]
+    pub bg_color: Option<String>,
+    #[serde(default)]
+    pub field_instances: Vec<LdtkField>,
+    /// `None` when the project saves levels to separate files
+    #[serde(default)]
+    pub layer_instances: Option<Vec<LdtkLayer>>,
+    #[serde(default, rename = "__neighbours")]
+    pub neighbours: Vec<LdtkNeighbour>,
+}
+
+/// A layer instance inside a level
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LdtkLayer {
+    #[serde(rename = "__identifier")]
+    pub identifier: String,
+    #[serde(rename = "__type")]
+    pub layer_type: String,
+    #[serde(rename = "__cWid")]
+    pub columns: u32,
+    #[serde(rename = "__cHei")]
+    pub rows: u32,
+    #[serde(rename = "__gridSize")]
+    pub grid_size: u32,
+    #[serde(default, rename = "__pxTotalOffsetX")]
+    pub offset_x: i32,
+    #[serde(default, rename = "__pxTotalOffsetY")]
+    pub offset_y: i32,
+    #[serde(default)]
+    pub int_grid_csv: Vec<i32>,
+    #[serde(default)]
+    pub entity_instances: Vec<LdtkEntity>,
+}
+
+/// An entity instance placed on an Entities layer
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LdtkEntity {
+    #[serde(rename = "__identifier")]
+    pub identifier: String,
+    pub iid: String,
+    /// Pivot position relative to the level, Y pointing down
+    pub px: [i32; 2],
+    #[serde(rename = "__pivot")]
+    pub pivot: [f32; 2],
+    pub width: u32,
+    pub height: u32,
+    #[serde(default)]
+    pub field_instances: Vec<LdtkField>,
+}
+
+/// A custom field value on a level or entity
+#[derive(Debug, Clone, Deserialize)]
+pub struct LdtkField {
+    #[serde(rename = "__identifier")]
+    pub identifier: String,
+    #[serde(rename = "__value")]
+    pub value: serde_json::Value,
+}
+
+/// A reference to an adjacent level
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LdtkNeighbour {
+    pub level_iid: String,
+    /// Direction of the neighbour: `n`, `s`, `e`, `w`, diagonals, `<`, `>` or `o`
+    pub dir: String,
+}
+
+/// Errors produced while converting an LDtk project into levels
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum LdtkError {
+    #[error("project has no levels")]
+    NoLevels,
+    #[error(
+        "level \"{0}\" is saved in a separate file, disable \"Save levels to separate files\""
+    )]
+    ExternalLevel(String),
+    #[error(
+        "level \"{level}\", layer \"{layer}\": expected {expected} IntGrid cells, found {found}"
+    )]
+    CellCountMismatch {
+        level: String,
+        layer: String,
+        expected: usize,
+        found: usize,
+    },
+    #[error("level \"{level}\", entity \"{identifier}\" ({iid}): unknown entity identifier")]
+    UnknownEntity {
+        level: String,
+        identifier: String,
+        iid: String,
+    },
+    #[error("level \"{level}\", entity \"{identifier}\": field \"{field}\" {reason}")]
+    InvalidField {
+        level: String,
+        identifier: String,
+        field: String,
+        reason: String,
+    },
+    #[error("invalid color \"{0}\", expected #RRGGBB")]
+    InvalidColor(String),
+}
+
+impl LdtkProject {
+    /// Parses a project from LDtk JSON text
+    pub fn from_json(text: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(text)
+    }
+
+    /// Converts every level of the project, in project order, returning each
+    /// level identifier with its data. Neighbour references become asset
+    /// paths of the form `{project_path}#{identifier}`.
+    pub fn to_levels(&self, project_path: &str) -> Result<Vec<(String, LevelData)>, LdtkError> {
+        let levels = self.placed_levels();
+        if levels.is_empty() {
+            return Err(LdtkError::NoLevels);
+        }
+
+        let identifiers: HashMap<&str, &str> = levels
+            .iter()
+            .map(|(level, _)| (level.iid.as_str(), level.identifier.as_str()))
+            .collect();
+        let project_bg = match &self.bg_color {
+            Some(color) => parse_color(color)?,
+            None => default_background_color(),
+        };
+
+        levels
+            .iter()
+            .map(|(level, world_position)| {
+                let mut data = self.convert_level(level, *world_position, project_bg)?;
+                data.neighbours = level
+                    .neighbours
+                    .iter()
+                    .filter_map(|neighbour| identifiers.get(neighbour.level_iid.as_str()))
+                    .map(|identifier| format!("{project_path}#{identifier}"))
+                    .collect();
+                Ok((level.identifier.clone(), data))
+            })
+            .collect()
+    }
+
+    /// Returns every level with its top-left world position in LDtk pixels.
+    /// Linear layouts don't store positions, so levels are laid end to end.
+    fn placed_levels(&self) -> Vec<(&LdtkLevel, IVec2)> {
+        let mut placed = Vec::new();
+        let worlds = std::iter::once((self.world_layout.as_deref(), &self.levels)).chain(
+            self.worlds
+                .iter()
+                .map(|world| (world.world_layout.as_deref(), &world.levels)),
+        );
+
+        for (layout, levels) in worlds {
+            let mut cursor = IVec2::ZERO;
+            for level in levels {
+                let position = match layout {
+                    Some("LinearHorizontal") => {
+                        let position = cursor;
+                        cursor.x += level.px_wid as i32;
+                        position
+                    }
+                    Some("LinearVertical") => {
+                        let position = cursor;
+                        cursor.y += level.px_hei as i32;
+                        position
+                    }
+                    _ => IVec2::new(level.world_x, level.world_y),
+                };
+                placed.push((level, position));
+            }
+        }
+
+        placed
+    }
+
+    fn convert_level(
+        &self,
+        level: &LdtkLevel,
+        world_position: IVec2,
+        project_bg: [f32; 3],
+    ) -> Result<LevelData, LdtkError> {
+        let layers = level
+            .layer_instances
+            .as_ref()
+            .ok_or_else(|| LdtkError::ExternalLevel(level.identifier.clone()))?;
+
+        // LDtk's Y axis points down, so flip it around the world origin
+        let to_world =
+            |x: f32, y: f32| Vec2::new(world_position.x as f32 + x, -(world_position.y as f32 + y));
+        let level_size = Vec2::new(level.px_wid as f32, level.px_hei as f32);
+        let bounds = Rect::from_corners(to_world(0.0, 0.0), to_world(level_size.x, level_size.y));
+
+        let mut data = LevelData {
+            name: level.identifier.clone(),
+            time_limit: find_field(&level.field_instances, "time_limit")
+                .and_then(serde_json::Value::as_f64)
+                .map(|value| value as f32)
+                .unwrap_or_else(default_time_limit),
+            background_color: match &level.bg_color {
+                Some(color) => parse_color(color)?,
+                None => project_bg,
+            },
+            // Used when the level is entered directly rather than walked into
+            player_spawn: bounds.center(),
+            goal: None,
+            bounds: Some(bounds),
+            camera_bounds: Some(bounds),
+            world_bounds: Some(bounds),
+            neighbours: Vec::new(),
+            platforms: Vec::new(),
+            one_way_platforms: Vec::new(),
+            ramps: Vec::new(),
+            ladders: Vec::new(),
+            water: Vec::new(),
+            kill_zones: Vec::new(),
+            crates: Vec::new(),
+            pressure_plates: Vec::new(),
+            enemies: Vec::new(),
+            collectibles: Vec::new(),
+            tilemap: None,
+        };
+
+        for layer in layers {
+            match layer.layer_type.as_str() {
+                "IntGrid" => self.convert_int_grid(level, layer, world_position, &mut data)?,
+                "Entities" => {
+                    for entity in &layer.entity_instances {
+                        convert_entity(level, layer, entity, &to_world, &mut data)?;
+                    }
+                }
+                // Tiles and AutoLayers are purely visual
+                _ => {}
+            }
+        }
+
+        Ok(data)
+    }
+
+    fn convert_int_grid(
+        &self,
+        level: &LdtkLevel,
+        layer: &LdtkLayer,
+        world_position: IVec2,
+        data: &mut LevelData,
+    ) -> Result<(), LdtkError> {
+        let expected = (layer.columns * layer.rows) as usize;
+        if layer.int_grid_csv.len() != expected {
+            return Err(LdtkError::CellCountMismatch {
+                level: level.identifier.clone(),
+                layer: layer.identifier.clone(),
+                expected,
+                found: layer.int_grid_csv.len(),
+            });
+        }
+
+        let values_named = |name: &str| -> Vec<i32> {
+            self.defs
+                .layers
+                .iter()
+                .filter(|definition| definition.identifier == layer.identifier)
+                .flat_map(|definition| &definition.int_grid_values)
+                .filter(|value| {
+                    value
+                        .identifier
+                        .as_deref()
+                        .is_some_and(|identifier| normalize(identifier) == name)
+                })
+                .map(|value| value.value)
+                .collect()
+        };
+        let one_way_values = values_named("oneway");
+        let ladder_values = values_named("ladder");
+
+        let cell = |x: u32, y: u32| layer.int_grid_csv[(y * layer.columns + x) as usize];
+        let tile_size = Vec2::splat(layer.grid_size as f32);
+        // World position of the grid's bottom-left corner
+        let origin = Vec2::new(
+            (world_position.x + layer.offset_x) as f32,
+            -((world_position.y + layer.offset_y) as f32 + layer.rows as f32 * tile_size.y),
+        );
+
+        let solid = greedy_mesh(layer.columns, layer.rows, |x, y| {
+            let value = cell(x, y);
+            value != 0 && !one_way_values.contains(&value) && !ladder_values.contains(&value)
+        });
+        let one_way = greedy_mesh(layer.columns, layer.rows, |x, y| {
+            one_way_values.contains(&cell(x, y))
+        });
+        let ladders = greedy_mesh(layer.columns, layer.rows, |x, y| {
+            ladder_values.contains(&cell(x, y))
+        });
+
+        data.platforms.extend(
+            solid
+                .iter()
+                .map(|rect| rect.to_platform(tile_size, layer.rows, origin)),
+        );
+        data.one_way_platforms.extend(
+            one_way
+                .iter()
+                .map(|rect| rect.to_platform(tile_size, layer.rows, origin)),
+        );
+        data.ladders.extend(
+            ladders
+                .iter()
+                .map(|rect| LadderData::from(rect.to_platform(tile_size, layer.rows, origin))),
+        );
+        Ok(())
+    }
+}
+
+fn convert_entity(
+    level: &LdtkLevel,
+    layer: &LdtkLayer,
+    entity: &LdtkEntity,
+    to_world: &impl Fn(f32, f32) -> Vec2,
+    data: &mut LevelData,
+) -> Result<(), LdtkError> {
+    let size = Vec2::new(entity.width as f32, entity.height as f32);
+    let pivot = Vec2::from(entity.pivot);
+    let top_left = Vec2::new(
+        (entity.px[0] + layer.offset_x) as f32,
+        (entity.px[1] + layer.offset_y) as f32,
+    ) - pivot * size;
+    let center = top_left + size / 2.0;
+    let position = to_world(center.x, center.y);
+    let fields = EntityFields { level, entity };
+
+    match normalize(&entity.identifier).as_str() {
+        "player" | "playerstart" => data.player_spawn = position,
+        "goal" => {
+            data.goal = Some(GoalData {
+                position,
+                size: if size.x > 0.0 && size.y > 0.0 {
+                    size
+                } else {
+                    default_goal_size()
+                },
+            })
+        }
+        "enemy" => data.enemies.push(EnemyData {
+            position,
+            size: if size.x > 0.0 && size.y > 0.0 {
+                size
+            } else {
+                default_enemy_size()
+            },
+            patrol_distance: fields.f32("patrol_distance")?.unwrap_or(0.0),
+            speed: fields.f32("speed")?.unwrap_or_else(default_enemy_speed),
+            damage: fields.f32("damage")?.unwrap_or_else(default_enemy_damage),
+        }),
+        "coin" | "collectible" => data.collectibles.push(CollectibleData {
+            position,
+            points: fields
+                .u32("points")?
+                .unwrap_or_else(default_collectible_points),
+        }),
+        "platform" => data.platforms.push(PlatformData {
+            position,
+            size,
+            color: None,
+            material: None,
+        }),
+        "onewayplatform" => data.one_way_platforms.push(PlatformData {
+            position,
+            size,
+            color: None,
+            material: None,
+        }),
+        "ramp" => data.ramps.push(RampData {
+            position,
+            size,
+            left_height: fields.f32("left_height")?.unwrap_or(0.0),
+            right_height: fields.f32("right_height")?.unwrap_or(size.y),
+            color: None,
+            material: None,
+        }),
+        "ladder" => data.ladders.push(LadderData {
+            position,
+            size,
+            color: None,
+        }),
+        "water" => {
+            let defaults = WaterVolume::default();
+            data.water.push(WaterData {
+                position,
+                size,
+                physics: WaterVolume {
+                    gravity_scale: fields
+                        .f32("gravity_scale")?
+                        .unwrap_or(defaults.gravity_scale),
+                    buoyancy: fields.f32("buoyancy")?.unwrap_or(defaults.buoyancy),
+                    drag: fields.f32("drag")?.unwrap_or(defaults.drag),
+                },
+                color: None,
+            })
+        }
+        "killzone" => data.kill_zones.push(KillZoneData {
+            position,
+            size,
+            damage: fields.f32("damage")?,
+            color: None,
+        }),
+        "crate" => data.crates.push(CrateData {
+            position,
+            size: if size.x > 0.0 && size.y > 0.0 {
+                size
+            } else {
+                default_crate_size()
+            },
+            mass: fields.f32("mass")?.unwrap_or_else(default_crate_mass),
+            color: None,
+        }),
+        "pressureplate" => data.pressure_plates.push(PressurePlateData {
+            position,
+            size: if size.x > 0.0 && size.y > 0.0 {
+                size
+            } else {
+                default_pressure_plate_size()
+            },
+            color: None,
+        }),
+        _ => {
+            return Err(LdtkError::UnknownEntity {
+                level: level.identifier.clone(),
+                identifier: entity.identifier.clone(),
+                iid: entity.iid.clone(),
+            })
+        }
+    }
+
+    Ok(())
+}
+
+/// Typed access to an entity's fields with errors naming the entity
+struct EntityFields<'a> {
+    level: &'a LdtkLevel,
+    entity: &'a LdtkEntity,
+}
+
+impl EntityFields<'_> {
+    /// Returns the field value, treating `null` (an unset optional field) as missing
+    fn get(&self, name: &str) -> Option<&serde_json::Value> {
+        find_field(&self.entity.field_instances, name).filter(|value| !value.is_null())
+    }
+
+    fn error(&self, field: &str, reason: &str) -> LdtkError {
+        LdtkError::InvalidField {
+            level: self.level.identifier.clone(),
+            identifier: self.entity.identifier.clone(),
+            field: field.to_string(),
+            reason: reason.to_string(),
+        }
+    }
+
+    fn f32(&self, name: &str) -> Result<Option<f32>, LdtkError> {
+        self.get(name)
+            .map(|value| {
+                value
+                    .as_f64()
+                    .map(|value| value as f32)
+                    .ok_or_else(|| self.error(name, "must be a number"))
+            })
+            .transpose()
+    }
+
+    fn u32(&self, name: &str) -> Result<Option<u32>, LdtkError> {
+        self.get(name)
+            .map(|value| {
+                value
+                    .as_u64()
+                    .and_then(|value| u32::try_from(value).ok())
+                    .ok_or_else(|| self.error(name, "must be a non-negative integer"))
+            })
+            .transpose()
+    }
+}
+
+/// Lowercases an identifier and strips underscores so `PatrolDistance`
+/// matches `patrol_distance`
+fn normalize(identifier: &str) -> String {
+    identifier
+        .chars()
+        .filter(|c| *c != '_')
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+fn find_field<'a>(fields: &'a [LdtkField], name: &str) -> Option<&'a serde_json::Value> {
+    let name = normalize(name);
+    fields
+        .iter()
+        .find(|field| normalize(&field.identifier) == name)
+        .map(|field| &field.value)
+}
+
+/// Parses an LDtk `#RRGGBB` color into sRGB components
+fn parse_color(color: &str) -> Result<[f32; 3], LdtkError> {
+    color
+        .strip_prefix('#')
+        .and_then(parse_hex_rgb)
+        .ok_or_else(|| LdtkError::InvalidColor(color.to_string()))
+}
+
+/// Loads every level of an LDtk project as labeled [`LevelData`] assets.
+/// The project itself loads as its first level.
+#[derive(Default)]
+pub struct LdtkProjectLoader;
+
+impl AssetLoader for LdtkProjectLoader {
+    type Asset = LevelData;
+    type Settings = ();
+    type Error = LevelLoadError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let text = std::str::from_utf8(&bytes)?;
+
+        let project_path = load_context.path().to_string_lossy().replace('\\', "/");
+        let levels = LdtkProject::from_json(text)?.to_levels(&project_path)?;
+
+        let first = levels
+            .first()
+            .map(|(_, level)| level.clone())
+            .ok_or(LdtkError::NoLevels)?;
+        for (identifier, level) in levels {
+            level.validate()?;
+            load_context.add_labeled_asset(identifier, level);
+        }
+
+        Ok(first)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ldtk"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_identifiers() {
+        assert_eq!(normalize("PatrolDistance"), "patroldistance");
+        assert_eq!(normalize("patrol_distance"), "patroldistance");
+        assert_eq!(normalize("One_Way"), "oneway");
+    }
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(parse_color("#00FF00").unwrap(), [0.0, 1.0, 0.0]);
+        assert!(parse_color("#00FF00FF").is_err());
+    }
+}
@@ -0,0 +1,71 @@
+//! Asset loader for level files
+
+use bevy::asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext};
+use thiserror::Error;
+
+use super::{LdtkError, LevelData, LevelValidationError, TiledError};
+
+/// Loads [`LevelData`] from `.level.ron` and `.level.json` files
+#[derive(Default)]
+pub struct LevelAssetLoader;
+
+/// Errors produced while loading a level file or importing a map
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum LevelLoadError {
+    /// The file could not be read
+    #[error("could not read level file: {0}")]
+    Io(#[from] std::io::Error),
+    /// The file is not valid UTF-8
+    #[error("level file is not valid UTF-8: {0}")]
+    Utf8(#[from] std::str::Utf8Error),
+    /// The RON syntax or structure is invalid
+    #[error("could not parse RON level: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    /// The JSON syntax or structure is invalid
+    #[error("could not parse JSON level: {0}")]
+    Json(#[from] serde_json::Error),
+    /// The Tiled map could not be converted into a level
+    #[error("could not import Tiled map: {0}")]
+    Tiled(#[from] TiledError),
+    /// The LDtk project could not be converted into levels
+    #[error("could not import LDtk project: {0}")]
+    Ldtk(#[from] LdtkError),
+    /// The level parsed but contains invalid values
+    #[error("invalid level: {0}")]
+    Invalid(#[from] LevelValidationError),
+}
+
+impl AssetLoader for LevelAssetLoader {
+    type Asset = LevelData;
+    type Settings = ();
+    type Error = LevelLoadError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let text = std::str::from_utf8(&bytes)?;
+
+        let is_json = load_context
+            .path()
+            .extension()
+            .is_some_and(|extension| extension == "json");
+        let level = if is_json {
+            LevelData::from_json(text)?
+        } else {
+            LevelData::from_ron(text)?
+        };
+
+        level.validate()?;
+        Ok(level)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["level.ron", "level.json"]
+    }
+}
@@ -0,0 +1,680 @@
+//! Level Data
+//!
+//! This module contains the data-driven level format. Levels are
+//! authored as RON or JSON asset files and loaded through Bevy's
+//! asset system by the [`LevelAssetLoader`], or imported from Tiled
+//! maps by the [`TiledMapLoader`] and LDtk projects by the
+//! [`LdtkProjectLoader`].
+
+pub mod grid;
+pub mod ldtk;
+mod loader;
+pub mod tiled;
+pub mod tilemap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::components::{SurfaceMaterial, WaterVolume};
+
+pub use ldtk::{LdtkError, LdtkProject, LdtkProjectLoader};
+pub use loader::{LevelAssetLoader, LevelLoadError};
+pub use tiled::{TiledError, TiledMap, TiledMapLoader};
+pub use tilemap::{TileAtlasData, TileData, TileMapData};
+
+/// A level loaded from a `.level.ron` or `.level.json` asset file
+#[derive(Asset, TypePath, Debug, Clone, Serialize, Deserialize)]
+pub struct LevelData {
+    /// Display name shown on the results screen
+    pub name: String,
+    /// Time limit in seconds used to compute the time bonus
+    #[serde(default = "default_time_limit")]
+    pub time_limit: f32,
+    /// Clear color behind the level as sRGB components
+    #[serde(default = "default_background_color")]
+    pub background_color: [f32; 3],
+    /// Where the player is placed when the level starts
+    pub player_spawn: Vec2,
+    /// The finish flag that ends the level, if this level has one
+    #[serde(default)]
+    pub goal: Option<GoalData>,
+    /// Region the camera is kept within, if any
+    #[serde(default)]
+    pub camera_bounds: Option<Rect>,
+    /// Area entities must stay within. The player is hurt or killed and
+    /// respawned for leaving it, anything else is despawned.
+    #[serde(default)]
+    pub bounds: Option<Rect>,
+    /// Area covered by the level in world space. Walking out of it into a
+    /// neighbour's area switches the active level without reloading.
+    #[serde(default)]
+    pub world_bounds: Option<Rect>,
+    /// Asset paths of the levels that can be walked into from this one
+    #[serde(default)]
+    pub neighbours: Vec<String>,
+    /// Solid platforms
+    #[serde(default)]
+    pub platforms: Vec<PlatformData>,
+    /// Platforms that can be jumped through from below and dropped through
+    #[serde(default)]
+    pub one_way_platforms: Vec<PlatformData>,
+    /// Solid slopes that can be walked up and down
+    #[serde(default)]
+    pub ramps: Vec<RampData>,
+    /// Ladders and other areas the player can climb
+    #[serde(default)]
+    pub ladders: Vec<LadderData>,
+    /// Water that entities swim in
+    #[serde(default)]
+    pub water: Vec<WaterData>,
+    /// Areas that hurt or kill the player and respawn them
+    #[serde(default)]
+    pub kill_zones: Vec<KillZoneData>,
+    /// Crates the player can push and stand on
+    #[serde(default)]
+    pub crates: Vec<CrateData>,
+    /// Switches held down by anything standing on them
+    #[serde(default)]
+    pub pressure_plates: Vec<PressurePlateData>,
+    #[serde(default)]
+    pub enemies: Vec<EnemyData>,
+    #[serde(default)]
+    pub collectibles: Vec<CollectibleData>,
+    /// Tile grid drawn from a tileset, with colliders built from its tiles
+    #[serde(default)]
+    pub tilemap: Option<TileMapData>,
+}
+
+fn default_time_limit() -> f32 {
+    120.0
+}
+
+fn default_background_color() -> [f32; 3] {
+    [0.17, 0.17, 0.18]
+}
+
+/// A rectangular platform centered on `position`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlatformData {
+    pub position: Vec2,
+    pub size: Vec2,
+    #[serde(default)]
+    pub color: Option<[f32; 3]>,
+    /// Ice, mud, bounce pad or conveyor surface
+    #[serde(default)]
+    pub material: Option<SurfaceMaterial>,
+}
+
+/// A solid ramp filling the bottom of a box centered on `position`. Its top
+/// edge runs from `left_height` above the box's bottom on the left to
+/// `right_height` on the right, so a 45° ramp is a square box with heights
+/// `0` and the box's width.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RampData {
+    pub position: Vec2,
+    pub size: Vec2,
+    pub left_height: f32,
+    pub right_height: f32,
+    #[serde(default)]
+    pub color: Option<[f32; 3]>,
+    #[serde(default)]
+    pub material: Option<SurfaceMaterial>,
+}
+
+/// A climbable area centered on `position`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LadderData {
+    pub position: Vec2,
+    pub size: Vec2,
+    #[serde(default)]
+    pub color: Option<[f32; 3]>,
+}
+
+impl From<PlatformData> for LadderData {
+    fn from(area: PlatformData) -> Self {
+        Self {
+            position: area.position,
+            size: area.size,
+            color: area.color,
+        }
+    }
+}
+
+/// A body of water centered on `position`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WaterData {
+    pub position: Vec2,
+    pub size: Vec2,
+    /// Gravity scale, buoyancy and drag
+    #[serde(default)]
+    pub physics: WaterVolume,
+    #[serde(default)]
+    pub color: Option<[f32; 3]>,
+}
+
+/// A kill zone centered on `position`, such as a pit or a lava lake
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KillZoneData {
+    pub position: Vec2,
+    pub size: Vec2,
+    /// Damage dealt on entering, or `None` to kill outright
+    #[serde(default)]
+    pub damage: Option<f32>,
+    /// Kill zones are invisible unless given a color
+    #[serde(default)]
+    pub color: Option<[f32; 3]>,
+}
+
+/// A pushable crate centered on `position`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrateData {
+    pub position: Vec2,
+    #[serde(default = "default_crate_size")]
+    pub size: Vec2,
+    /// Heavier crates are pushed more slowly
+    #[serde(default = "default_crate_mass")]
+    pub mass: f32,
+    #[serde(default)]
+    pub color: Option<[f32; 3]>,
+}
+
+fn default_crate_size() -> Vec2 {
+    Vec2::splat(40.0)
+}
+
+fn default_crate_mass() -> f32 {
+    1.0
+}
+
+/// A pressure plate centered on `position`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PressurePlateData {
+    pub position: Vec2,
+    #[serde(default = "default_pressure_plate_size")]
+    pub size: Vec2,
+    #[serde(default)]
+    pub color: Option<[f32; 3]>,
+}
+
+fn default_pressure_plate_size() -> Vec2 {
+    Vec2::new(40.0, 10.0)
+}
+
+/// The level goal centered on `position`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GoalData {
+    pub position: Vec2,
+    #[serde(default = "default_goal_size")]
+    pub size: Vec2,
+}
+
+fn default_goal_size() -> Vec2 {
+    Vec2::new(30.0, 80.0)
+}
+
+/// An enemy that patrols back and forth around its spawn position
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnemyData {
+    pub position: Vec2,
+    #[serde(default = "default_enemy_size")]
+    pub size: Vec2,
+    /// Distance travelled either side of `position` (0 = stationary)
+    #[serde(default)]
+    pub patrol_distance: f32,
+    #[serde(default = "default_enemy_speed")]
+    pub speed: f32,
+    #[serde(default = "default_enemy_damage")]
+    pub damage: f32,
+}
+
+fn default_enemy_size() -> Vec2 {
+    Vec2::new(40.0, 40.0)
+}
+
+fn default_enemy_speed() -> f32 {
+    80.0
+}
+
+fn default_enemy_damage() -> f32 {
+    20.0
+}
+
+/// A pickup that awards points when touched
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectibleData {
+    pub position: Vec2,
+    #[serde(default = "default_collectible_points")]
+    pub points: u32,
+}
+
+fn default_collectible_points() -> u32 {
+    50
+}
+
+/// A validation error pointing at the offending entry of a level file
+#[derive(Debug, Clone, PartialEq, Error)]
+#[error("{entry}: {reason}")]
+pub struct LevelValidationError {
+    /// Path to the entry, e.g. `platforms[2].size`
+    pub entry: String,
+    pub reason: String,
+}
+
+impl LevelValidationError {
+    fn new(entry: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self {
+            entry: entry.into(),
+            reason: reason.into(),
+        }
+    }
+}
+
+impl LevelData {
+    /// Parses a level from RON text. Optional fields may be written
+    /// without `Some(...)`.
+    pub fn from_ron(text: &str) -> Result<Self, ron::error::SpannedError> {
+        ron::Options::default()
+            .with_default_extension(ron::extensions::Extensions::IMPLICIT_SOME)
+            .from_str(text)
+    }
+
+    /// Parses a level from JSON text
+    pub fn from_json(text: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(text)
+    }
+
+    /// Checks the level for values that would produce a broken layout
+    pub fn validate(&self) -> Result<(), LevelValidationError> {
+        if self.name.trim().is_empty() {
+            return Err(LevelValidationError::new("name", "must not be empty"));
+        }
+        if !(self.time_limit.is_finite() && self.time_limit > 0.0) {
+            return Err(LevelValidationError::new(
+                "time_limit",
+                format!("must be positive, got {}", self.time_limit),
+            ));
+        }
+        for (i, channel) in self.background_color.iter().enumerate() {
+            if !(0.0..=1.0).contains(channel) {
+                return Err(LevelValidationError::new(
+                    format!("background_color[{i}]"),
+                    format!("must be between 0 and 1, got {channel}"),
+                ));
+            }
+        }
+        check_position("player_spawn", self.player_spawn)?;
+        if let Some(goal) = &self.goal {
+            check_position("goal.position", goal.position)?;
+            check_size("goal.size", goal.size)?;
+        }
+        check_bounds("bounds", self.bounds)?;
+        check_bounds("camera_bounds", self.camera_bounds)?;
+        check_bounds("world_bounds", self.world_bounds)?;
+
+        for (i, neighbour) in self.neighbours.iter().enumerate() {
+            if neighbour.trim().is_empty() {
+                return Err(LevelValidationError::new(
+                    format!("neighbours[{i}]"),
+                    "must not be empty",
+                ));
+            }
+        }
+
+        for (field, platforms) in [
+            ("platforms", &self.platforms),
+            ("one_way_platforms", &self.one_way_platforms),
+        ] {
+            for (i, platform) in platforms.iter().enumerate() {
+                check_position(&format!("{field}[{i}].position"), platform.position)?;
+                check_size(&format!("{field}[{i}].size"), platform.size)?;
+                check_material(&format!("{field}[{i}].material"), platform.material)?;
+            }
+        }
+
+        for (i, ladder) in self.ladders.iter().enumerate() {
+            check_position(&format!("ladders[{i}].position"), ladder.position)?;
+            check_size(&format!("ladders[{i}].size"), ladder.size)?;
+        }
+
+        for (i, water) in self.water.iter().enumerate() {
+            check_position(&format!("water[{i}].position"), water.position)?;
+            check_size(&format!("water[{i}].size"), water.size)?;
+            let physics = water.physics;
+            for (field, value) in [
+                ("gravity_scale", physics.gravity_scale),
+                ("buoyancy", physics.buoyancy),
+                ("drag", physics.drag),
+            ] {
+                if !(value.is_finite() && value >= 0.0) {
+                    return Err(LevelValidationError::new(
+                        format!("water[{i}].physics.{field}"),
+                        format!("must not be negative, got {value}"),
+                    ));
+                }
+            }
+        }
+
+        for (i, zone) in self.kill_zones.iter().enumerate() {
+            check_position(&format!("kill_zones[{i}].position"), zone.position)?;
+            check_size(&format!("kill_zones[{i}].size"), zone.size)?;
+            if let Some(damage) = zone
+                .damage
+                .filter(|damage| !(damage.is_finite() && *damage >= 0.0))
+            {
+                return Err(LevelValidationError::new(
+                    format!("kill_zones[{i}].damage"),
+                    format!("must not be negative, got {damage}"),
+                ));
+            }
+        }
+
+        for (i, crate_data) in self.crates.iter().enumerate() {
+            check_position(&format!("crates[{i}].position"), crate_data.position)?;
+            check_size(&format!("crates[{i}].size"), crate_data.size)?;
+            if !(crate_data.mass.is_finite() && crate_data.mass > 0.0) {
+                return Err(LevelValidationError::new(
+                    format!("crates[{i}].mass"),
+                    format!("must be positive, got {}", crate_data.mass),
+                ));
+            }
+        }
+
+        for (i, plate) in self.pressure_plates.iter().enumerate() {
+            check_position(&format!("pressure_plates[{i}].position"), plate.position)?;
+            check_size(&format!("pressure_plates[{i}].size"), plate.size)?;
+        }
+
+        for (i, ramp) in self.ramps.iter().enumerate() {
+            check_position(&format!("ramps[{i}].position"), ramp.position)?;
+            check_size(&format!("ramps[{i}].size"), ramp.size)?;
+            check_material(&format!("ramps[{i}].material"), ramp.material)?;
+            for (field, height) in [
+                ("left_height", ramp.left_height),
+                ("right_height", ramp.right_height),
+            ] {
+                if !(0.0..=ramp.size.y).contains(&height) {
+                    return Err(LevelValidationError::new(
+                        format!("ramps[{i}].{field}"),
+                        format!(
+                            "must be between 0 and the ramp's height {}, got {height}",
+                            ramp.size.y
+                        ),
+                    ));
+                }
+            }
+        }
+
+        for (i, enemy) in self.enemies.iter().enumerate() {
+            check_position(&format!("enemies[{i}].position"), enemy.position)?;
+            check_size(&format!("enemies[{i}].size"), enemy.size)?;
+            if !(enemy.patrol_distance.is_finite() && enemy.patrol_distance >= 0.0) {
+                return Err(LevelValidationError::new(
+                    format!("enemies[{i}].patrol_distance"),
+                    format!("must not be negative, got {}", enemy.patrol_distance),
+                ));
+            }
+            if !(enemy.speed.is_finite() && enemy.speed >= 0.0) {
+                return Err(LevelValidationError::new(
+                    format!("enemies[{i}].speed"),
+                    format!("must not be negative, got {}", enemy.speed),
+                ));
+            }
+            if !(enemy.damage.is_finite() && enemy.damage >= 0.0) {
+                return Err(LevelValidationError::new(
+                    format!("enemies[{i}].damage"),
+                    format!("must not be negative, got {}", enemy.damage),
+                ));
+            }
+        }
+
+        for (i, collectible) in self.collectibles.iter().enumerate() {
+            check_position(&format!("collectibles[{i}].position"), collectible.position)?;
+        }
+
+        if let Some(tilemap) = &self.tilemap {
+            tilemap.validate()?;
+        }
+
+        Ok(())
+    }
+}
+
+fn check_position(entry: &str, position: Vec2) -> Result<(), LevelValidationError> {
+    if position.is_finite() {
+        Ok(())
+    } else {
+        Err(LevelValidationError::new(
+            entry,
+            format!("must be finite, got {position}"),
+        ))
+    }
+}
+
+fn check_bounds(entry: &str, bounds: Option<Rect>) -> Result<(), LevelValidationError> {
+    match bounds {
+        Some(bounds)
+            if !(bounds.min.is_finite() && bounds.max.is_finite())
+                || bounds.min.x >= bounds.max.x
+                || bounds.min.y >= bounds.max.y =>
+        {
+            Err(LevelValidationError::new(
+                entry,
+                format!("min {} must be below max {}", bounds.min, bounds.max),
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn check_size(entry: &str, size: Vec2) -> Result<(), LevelValidationError> {
+    if size.is_finite() && size.x > 0.0 && size.y > 0.0 {
+        Ok(())
+    } else {
+        Err(LevelValidationError::new(
+            entry,
+            format!("must be positive, got {size}"),
+        ))
+    }
+}
+
+fn check_material(
+    entry: &str,
+    material: Option<SurfaceMaterial>,
+) -> Result<(), LevelValidationError> {
+    let Some(material) = material else {
+        return Ok(());
+    };
+    if !(material.friction.is_finite() && material.friction >= 0.0) {
+        return Err(LevelValidationError::new(
+            format!("{entry}.friction"),
+            format!("must not be negative, got {}", material.friction),
+        ));
+    }
+    if !(material.bounciness.is_finite() && material.bounciness >= 0.0) {
+        return Err(LevelValidationError::new(
+            format!("{entry}.bounciness"),
+            format!("must not be negative, got {}", material.bounciness),
+        ));
+    }
+    if !material.conveyor.is_finite() {
+        return Err(LevelValidationError::new(
+            format!("{entry}.conveyor"),
+            format!("must be finite, got {}", material.conveyor),
+        ));
+    }
+    Ok(())
+}
+
+/// Parses six `RRGGBB` hex digits into sRGB components, shared by the
+/// Tiled and LDtk importers
+fn parse_hex_rgb(hex: &str) -> Option<[f32; 3]> {
+    if hex.len() != 6 {
+        return None;
+    }
+    let mut channels = [0.0; 3];
+    for (i, channel) in channels.iter_mut().enumerate() {
+        let byte = u8::from_str_radix(hex.get(i * 2..i * 2 + 2)?, 16).ok()?;
+        *channel = byte as f32 / 255.0;
+    }
+    Some(channels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINIMAL_LEVEL: &str = r#"(
+        name: "Test",
+        player_spawn: (0.0, 0.0),
+        goal: (position: (100.0, 0.0)),
+        platforms: [
+            (position: (0.0, -50.0), size: (200.0, 20.0)),
+        ],
+    )"#;
+
+    #[test]
+    fn test_parse_minimal_level_uses_defaults() {
+        let level = LevelData::from_ron(MINIMAL_LEVEL).unwrap();
+        assert_eq!(level.time_limit, 120.0);
+        assert_eq!(level.goal.as_ref().unwrap().size, Vec2::new(30.0, 80.0));
+        assert!(level.camera_bounds.is_none());
+        assert!(level.enemies.is_empty());
+        assert!(level.validate().is_ok());
+    }
+
+    #[test]
+    fn test_parse_json_level() {
+        let json = r#"{
+            "name": "Json",
+            "player_spawn": [0.0, 10.0],
+            "goal": { "position": [50.0, 0.0] },
+            "collectibles": [{ "position": [10.0, 10.0], "points": 5 }]
+        }"#;
+        let level = LevelData::from_json(json).unwrap();
+        assert_eq!(level.player_spawn, Vec2::new(0.0, 10.0));
+        assert_eq!(level.collectibles[0].points, 5);
+    }
+
+    #[test]
+    fn test_validate_points_to_offending_platform() {
+        let mut level = LevelData::from_ron(MINIMAL_LEVEL).unwrap();
+        level.platforms.push(PlatformData {
+            position: Vec2::ZERO,
+            size: Vec2::new(0.0, 20.0),
+            color: None,
+            material: None,
+        });
+
+        let error = level.validate().unwrap_err();
+        assert_eq!(error.entry, "platforms[1].size");
+    }
+
+    #[test]
+    fn test_parse_platform_material() {
+        let mut level = LevelData::from_ron(
+            r#"(
+                name: "Ice",
+                player_spawn: (0.0, 0.0),
+                platforms: [
+                    (position: (0.0, -50.0), size: (200.0, 20.0), material: Some((friction: 0.1))),
+                ],
+            )"#,
+        )
+        .unwrap();
+        let material = level.platforms[0].material.unwrap();
+        assert_eq!(material.friction, 0.1);
+        assert_eq!(material.bounciness, 0.0);
+        assert!(level.validate().is_ok());
+
+        level.platforms[0].material = Some(SurfaceMaterial {
+            bounciness: -1.0,
+            ..default()
+        });
+        let error = level.validate().unwrap_err();
+        assert_eq!(error.entry, "platforms[0].material.bounciness");
+    }
+
+    #[test]
+    fn test_validate_rejects_negative_kill_zone_damage() {
+        let mut level = LevelData::from_ron(MINIMAL_LEVEL).unwrap();
+        level.kill_zones.push(KillZoneData {
+            position: Vec2::ZERO,
+            size: Vec2::new(100.0, 50.0),
+            damage: None,
+            color: None,
+        });
+        assert!(level.validate().is_ok());
+
+        level.kill_zones[0].damage = Some(-5.0);
+        let error = level.validate().unwrap_err();
+        assert_eq!(error.entry, "kill_zones[0].damage");
+    }
+
+    #[test]
+    fn test_validate_rejects_weightless_crate() {
+        let mut level = LevelData::from_ron(
+            r#"(
+                name: "Crates",
+                player_spawn: (0.0, 0.0),
+                crates: [(position: (100.0, 0.0))],
+            )"#,
+        )
+        .unwrap();
+        assert_eq!(level.crates[0].size, Vec2::splat(40.0));
+        assert!(level.validate().is_ok());
+
+        level.crates[0].mass = 0.0;
+        let error = level.validate().unwrap_err();
+        assert_eq!(error.entry, "crates[0].mass");
+    }
+
+    #[test]
+    fn test_validate_rejects_ramp_taller_than_its_box() {
+        let mut level = LevelData::from_ron(MINIMAL_LEVEL).unwrap();
+        level.ramps.push(RampData {
+            position: Vec2::ZERO,
+            size: Vec2::new(100.0, 50.0),
+            left_height: 0.0,
+            right_height: 50.0,
+            color: None,
+            material: None,
+        });
+        assert!(level.validate().is_ok());
+
+        level.ramps[0].left_height = 60.0;
+        let error = level.validate().unwrap_err();
+        assert_eq!(error.entry, "ramps[0].left_height");
+    }
+
+    #[test]
+    fn test_parse_level_without_goal() {
+        let level = LevelData::from_ron(
+            r#"(name: "Corridor", player_spawn: (0.0, 0.0), neighbours: ["world.ldtk#Level_1"])"#,
+        )
+        .unwrap();
+        assert!(level.goal.is_none());
+        assert_eq!(level.neighbours, vec!["world.ldtk#Level_1".to_string()]);
+        assert!(level.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_rejects_inverted_camera_bounds() {
+        let mut level = LevelData::from_ron(MINIMAL_LEVEL).unwrap();
+        level.camera_bounds = Some(Rect {
+            min: Vec2::new(10.0, 0.0),
+            max: Vec2::new(-10.0, 100.0),
+        });
+
+        let error = level.validate().unwrap_err();
+        assert_eq!(error.entry, "camera_bounds");
+    }
+
+    #[test]
+    fn test_parse_hex_rgb() {
+        assert_eq!(parse_hex_rgb("ff8000"), Some([1.0, 128.0 / 255.0, 0.0]));
+        assert_eq!(parse_hex_rgb("ff80"), None);
+        assert_eq!(parse_hex_rgb("gg0000"), None);
+        assert_eq!(parse_hex_rgb("é0000"), None);
+    }
+}
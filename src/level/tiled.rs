@@ -0,0 +1,559 @@
+//! Importer for maps made with the Tiled editor, saved as Tiled JSON (`.tmj`)
+//!
+//! Tile layers become merged platform rectangles. Object layers spawn typed
+//! entities based on each object's class (`player_start`, `goal`, `enemy`,
+//! `coin`, `platform`, `one_way_platform`, `ramp`, `ladder`, `water`,
+//! `kill_zone`, `crate`, `pressure_plate`) with custom properties mapped to the matching level fields.
+//! The bottom-left corner of the map is placed at the world origin.
+
+use bevy::asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext};
+use bevy::prelude::*;
+use serde::Deserialize;
+use thiserror::Error;
+
+use super::grid::greedy_mesh;
+use super::{
+    default_background_color, default_collectible_points, default_crate_mass, default_crate_size,
+    default_enemy_damage, default_enemy_size, default_enemy_speed, default_goal_size,
+    default_pressure_plate_size, default_time_limit, parse_hex_rgb, CollectibleData, CrateData,
+    EnemyData, GoalData, KillZoneData, LadderData, LevelData, LevelLoadError, PlatformData,
+    PressurePlateData, RampData, WaterData,
+};
+use crate::components::WaterVolume;
+
+/// Bits Tiled stores in a global tile ID to flip or rotate the tile
+const GID_FLAGS_MASK: u32 = 0xF000_0000;
+
+/// A Tiled map as stored in a `.tmj` file
+#[derive(Debug, Clone, Deserialize)]
+pub struct TiledMap {
+    pub width: u32,
+    pub height: u32,
+    #[serde(rename = "tilewidth")]
+    pub tile_width: u32,
+    #[serde(rename = "tileheight")]
+    pub tile_height: u32,
+    #[serde(default)]
+    pub infinite: bool,
+    #[serde(default, rename = "backgroundcolor")]
+    pub background_color: Option<String>,
+    #[serde(default)]
+    pub layers: Vec<TiledLayer>,
+    #[serde(default)]
+    pub properties: Vec<TiledProperty>,
+}
+
+/// A layer of a Tiled map
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum TiledLayer {
+    TileLayer {
+        name: String,
+        width: u32,
+        height: u32,
+        #[serde(default)]
+        data: TiledLayerData,
+        #[serde(default)]
+        encoding: Option<String>,
+        #[serde(default)]
+        properties: Vec<TiledProperty>,
+    },
+    ObjectGroup {
+        name: String,
+        #[serde(default)]
+        objects: Vec<TiledObject>,
+    },
+    /// Image and group layers carry no gameplay data
+    #[serde(other)]
+    Other,
+}
+
+/// Tile layer data: a list of tile IDs in the CSV layer format, or an
+/// encoded string for the base64 formats
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum TiledLayerData {
+    Csv(Vec<u32>),
+    Encoded(String),
+}
+
+impl Default for TiledLayerData {
+    fn default() -> Self {
+        Self::Csv(Vec::new())
+    }
+}
+
+/// An object placed on an object layer
+#[derive(Debug, Clone, Deserialize)]
+pub struct TiledObject {
+    pub id: u32,
+    #[serde(default)]
+    pub name: String,
+    /// The object's class (`class` in Tiled 1.9, `type` otherwise)
+    #[serde(default, rename = "type", alias = "class")]
+    pub class: String,
+    pub x: f32,
+    pub y: f32,
+    #[serde(default)]
+    pub width: f32,
+    #[serde(default)]
+    pub height: f32,
+    /// Set for tile objects, whose origin is the bottom-left corner
+    #[serde(default)]
+    pub gid: Option<u32>,
+    #[serde(default)]
+    pub properties: Vec<TiledProperty>,
+}
+
+/// A custom property attached to a map, layer or object
+#[derive(Debug, Clone, Deserialize)]
+pub struct TiledProperty {
+    pub name: String,
+    pub value: serde_json::Value,
+}
+
+/// Errors produced while converting a Tiled map into a level
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum TiledError {
+    #[error("infinite maps are not supported, disable \"Infinite\" in the map properties")]
+    InfiniteMap,
+    #[error("layer \"{layer}\": {encoding} tile data is not supported, save the map with CSV layer format")]
+    UnsupportedEncoding { layer: String, encoding: String },
+    #[error("layer \"{layer}\": expected {expected} tiles, found {found}")]
+    TileCountMismatch {
+        layer: String,
+        expected: usize,
+        found: usize,
+    },
+    #[error("layer \"{layer}\", object {id}: unknown object class \"{class}\"")]
+    UnknownObjectClass {
+        layer: String,
+        id: u32,
+        class: String,
+    },
+    #[error("layer \"{layer}\", object {id}: property \"{property}\" {reason}")]
+    InvalidProperty {
+        layer: String,
+        id: u32,
+        property: String,
+        reason: String,
+    },
+    #[error("map has no object with class \"{0}\"")]
+    MissingObject(&'static str),
+    #[error("invalid background color \"{0}\", expected #RRGGBB or #AARRGGBB")]
+    InvalidColor(String),
+}
+
+impl TiledMap {
+    /// Parses a map from Tiled JSON text
+    pub fn from_json(text: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(text)
+    }
+
+    /// Size of the whole map in pixels
+    pub fn pixel_size(&self) -> Vec2 {
+        Vec2::new(
+            (self.width * self.tile_width) as f32,
+            (self.height * self.tile_height) as f32,
+        )
+    }
+
+    /// Converts the map into level data, using `default_name` if the map
+    /// has no `name` property
+    pub fn to_level(&self, default_name: &str) -> Result<LevelData, TiledError> {
+        if self.infinite {
+            return Err(TiledError::InfiniteMap);
+        }
+
+        let tile_size = Vec2::new(self.tile_width as f32, self.tile_height as f32);
+        let map_size = self.pixel_size();
+
+        let mut level = LevelData {
+            name: property_str(&self.properties, "name")
+                .unwrap_or(default_name)
+                .to_string(),
+            time_limit: property_f32(&self.properties, "time_limit")
+                .unwrap_or_else(default_time_limit),
+            background_color: match &self.background_color {
+                Some(color) => parse_color(color)?,
+                None => default_background_color(),
+            },
+            player_spawn: Vec2::ZERO,
+            goal: None,
+            bounds: Some(Rect::from_corners(Vec2::ZERO, map_size)),
+            camera_bounds: Some(Rect::from_corners(Vec2::ZERO, map_size)),
+            world_bounds: None,
+            neighbours: Vec::new(),
+            platforms: Vec::new(),
+            one_way_platforms: Vec::new(),
+            ramps: Vec::new(),
+            ladders: Vec::new(),
+            water: Vec::new(),
+            kill_zones: Vec::new(),
+            crates: Vec::new(),
+            pressure_plates: Vec::new(),
+            enemies: Vec::new(),
+            collectibles: Vec::new(),
+            tilemap: None,
+        };
+        let mut player_spawn = None;
+
+        for layer in &self.layers {
+            match layer {
+                TiledLayer::TileLayer {
+                    name,
+                    width,
+                    height,
+                    data,
+                    encoding,
+                    properties,
+                } => {
+                    if let Some(encoding) = encoding.as_ref().filter(|e| *e != "csv") {
+                        return Err(TiledError::UnsupportedEncoding {
+                            layer: name.clone(),
+                            encoding: encoding.clone(),
+                        });
+                    }
+                    let TiledLayerData::Csv(data) = data else {
+                        return Err(TiledError::UnsupportedEncoding {
+                            layer: name.clone(),
+                            encoding: "base64".to_string(),
+                        });
+                    };
+                    let expected = (*width * *height) as usize;
+                    if data.len() != expected {
+                        return Err(TiledError::TileCountMismatch {
+                            layer: name.clone(),
+                            expected,
+                            found: data.len(),
+                        });
+                    }
+                    if property_bool(properties, "collision") == Some(false) {
+                        continue;
+                    }
+
+                    let rects = greedy_mesh(*width, *height, |x, y| {
+                        data[(y * *width + x) as usize] & !GID_FLAGS_MASK != 0
+                    });
+                    let platforms = rects
+                        .iter()
+                        .map(|rect| rect.to_platform(tile_size, *height, Vec2::ZERO));
+                    if property_bool(properties, "ladder") == Some(true) {
+                        level.ladders.extend(platforms.map(LadderData::from));
+                    } else if property_bool(properties, "one_way") == Some(true) {
+                        level.one_way_platforms.extend(platforms);
+                    } else {
+                        level.platforms.extend(platforms);
+                    }
+                }
+                TiledLayer::ObjectGroup { name, objects } => {
+                    for object in objects {
+                        let props = ObjectProperties {
+                            layer: name,
+                            object,
+                        };
+                        let center = object.center(map_size.y);
+                        let size = Vec2::new(object.width, object.height);
+
+                        match object.class.as_str() {
+                            "player_start" => player_spawn = Some(center),
+                            "goal" => {
+                                level.goal = Some(GoalData {
+                                    position: center,
+                                    size: if size.x > 0.0 && size.y > 0.0 {
+                                        size
+                                    } else {
+                                        default_goal_size()
+                                    },
+                                })
+                            }
+                            "enemy" => {
+                                let mut enemy = EnemyData {
+                                    position: center,
+                                    size: default_enemy_size(),
+                                    patrol_distance: 0.0,
+                                    speed: default_enemy_speed(),
+                                    damage: default_enemy_damage(),
+                                };
+                                if size.x > 0.0 && size.y > 0.0 {
+                                    enemy.size = size;
+                                }
+                                if let Some(distance) = props.f32("patrol_distance")? {
+                                    enemy.patrol_distance = distance;
+                                }
+                                if let Some(speed) = props.f32("speed")? {
+                                    enemy.speed = speed;
+                                }
+                                if let Some(damage) = props.f32("damage")? {
+                                    enemy.damage = damage;
+                                }
+                                level.enemies.push(enemy);
+                            }
+                            "coin" | "collectible" => level.collectibles.push(CollectibleData {
+                                position: center,
+                                points: props
+                                    .u32("points")?
+                                    .unwrap_or_else(default_collectible_points),
+                            }),
+                            "platform" | "one_way_platform" => {
+                                let platform = PlatformData {
+                                    position: center,
+                                    size,
+                                    color: None,
+                                    material: None,
+                                };
+                                if object.class == "platform" {
+                                    level.platforms.push(platform);
+                                } else {
+                                    level.one_way_platforms.push(platform);
+                                }
+                            }
+                            "ramp" => level.ramps.push(RampData {
+                                position: center,
+                                size,
+                                left_height: props.f32("left_height")?.unwrap_or(0.0),
+                                right_height: props.f32("right_height")?.unwrap_or(size.y),
+                                color: None,
+                                material: None,
+                            }),
+                            "ladder" => level.ladders.push(LadderData {
+                                position: center,
+                                size,
+                                color: None,
+                            }),
+                            "water" => {
+                                let mut physics = WaterVolume::default();
+                                if let Some(scale) = props.f32("gravity_scale")? {
+                                    physics.gravity_scale = scale;
+                                }
+                                if let Some(buoyancy) = props.f32("buoyancy")? {
+                                    physics.buoyancy = buoyancy;
+                                }
+                                if let Some(drag) = props.f32("drag")? {
+                                    physics.drag = drag;
+                                }
+                                level.water.push(WaterData {
+                                    position: center,
+                                    size,
+                                    physics,
+                                    color: None,
+                                });
+                            }
+                            "kill_zone" => level.kill_zones.push(KillZoneData {
+                                position: center,
+                                size,
+                                damage: props.f32("damage")?,
+                                color: None,
+                            }),
+                            "crate" => level.crates.push(CrateData {
+                                position: center,
+                                size: if size.x > 0.0 && size.y > 0.0 {
+                                    size
+                                } else {
+                                    default_crate_size()
+                                },
+                                mass: props.f32("mass")?.unwrap_or_else(default_crate_mass),
+                                color: None,
+                            }),
+                            "pressure_plate" => level.pressure_plates.push(PressurePlateData {
+                                position: center,
+                                size: if size.x > 0.0 && size.y > 0.0 {
+                                    size
+                                } else {
+                                    default_pressure_plate_size()
+                                },
+                                color: None,
+                            }),
+                            "camera_bounds" => {
+                                level.camera_bounds = Some(Rect::from_center_size(center, size));
+                            }
+                            "level_bounds" => {
+                                level.bounds = Some(Rect::from_center_size(center, size));
+                            }
+                            // Unclassed objects are annotations for the designer
+                            "" => {}
+                            class => {
+                                return Err(TiledError::UnknownObjectClass {
+                                    layer: name.clone(),
+                                    id: object.id,
+                                    class: class.to_string(),
+                                })
+                            }
+                        }
+                    }
+                }
+                TiledLayer::Other => {}
+            }
+        }
+
+        level.player_spawn = player_spawn.ok_or(TiledError::MissingObject("player_start"))?;
+        if level.goal.is_none() {
+            return Err(TiledError::MissingObject("goal"));
+        }
+        Ok(level)
+    }
+}
+
+impl TiledObject {
+    /// Center of the object in world space, flipping Tiled's downward Y axis
+    fn center(&self, map_height: f32) -> Vec2 {
+        // Tile objects are anchored at their bottom-left corner, everything
+        // else at the top-left corner
+        let top = if self.gid.is_some() {
+            self.y - self.height
+        } else {
+            self.y
+        };
+        Vec2::new(
+            self.x + self.width / 2.0,
+            map_height - (top + self.height / 2.0),
+        )
+    }
+}
+
+/// Typed access to an object's custom properties with errors naming the object
+struct ObjectProperties<'a> {
+    layer: &'a str,
+    object: &'a TiledObject,
+}
+
+impl ObjectProperties<'_> {
+    fn get(&self, name: &str) -> Option<&serde_json::Value> {
+        find_property(&self.object.properties, name)
+    }
+
+    fn error(&self, property: &str, reason: &str) -> TiledError {
+        TiledError::InvalidProperty {
+            layer: self.layer.to_string(),
+            id: self.object.id,
+            property: property.to_string(),
+            reason: reason.to_string(),
+        }
+    }
+
+    fn f32(&self, name: &str) -> Result<Option<f32>, TiledError> {
+        self.get(name)
+            .map(|value| {
+                value
+                    .as_f64()
+                    .map(|value| value as f32)
+                    .ok_or_else(|| self.error(name, "must be a number"))
+            })
+            .transpose()
+    }
+
+    fn u32(&self, name: &str) -> Result<Option<u32>, TiledError> {
+        self.get(name)
+            .map(|value| {
+                value
+                    .as_u64()
+                    .and_then(|value| u32::try_from(value).ok())
+                    .ok_or_else(|| self.error(name, "must be a non-negative integer"))
+            })
+            .transpose()
+    }
+}
+
+fn find_property<'a>(properties: &'a [TiledProperty], name: &str) -> Option<&'a serde_json::Value> {
+    properties
+        .iter()
+        .find(|property| property.name == name)
+        .map(|property| &property.value)
+}
+
+fn property_str<'a>(properties: &'a [TiledProperty], name: &str) -> Option<&'a str> {
+    find_property(properties, name).and_then(serde_json::Value::as_str)
+}
+
+fn property_f32(properties: &[TiledProperty], name: &str) -> Option<f32> {
+    find_property(properties, name)
+        .and_then(serde_json::Value::as_f64)
+        .map(|value| value as f32)
+}
+
+fn property_bool(properties: &[TiledProperty], name: &str) -> Option<bool> {
+    find_property(properties, name).and_then(serde_json::Value::as_bool)
+}
+
+/// Parses a Tiled `#RRGGBB` or `#AARRGGBB` color into sRGB components
+fn parse_color(color: &str) -> Result<[f32; 3], TiledError> {
+    color
+        .strip_prefix('#')
+        .and_then(|hex| match hex.len() {
+            8 => hex.get(2..),
+            _ => Some(hex),
+        })
+        .and_then(parse_hex_rgb)
+        .ok_or_else(|| TiledError::InvalidColor(color.to_string()))
+}
+
+/// Loads [`LevelData`] from Tiled JSON maps (`.tmj`)
+#[derive(Default)]
+pub struct TiledMapLoader;
+
+impl AssetLoader for TiledMapLoader {
+    type Asset = LevelData;
+    type Settings = ();
+    type Error = LevelLoadError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let text = std::str::from_utf8(&bytes)?;
+
+        let default_name = load_context
+            .path()
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or("Untitled")
+            .to_string();
+        let level = TiledMap::from_json(text)?.to_level(&default_name)?;
+
+        level.validate()?;
+        Ok(level)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["tmj"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(parse_color("#ff0000").unwrap(), [1.0, 0.0, 0.0]);
+        assert_eq!(parse_color("#8000ff00").unwrap(), [0.0, 1.0, 0.0]);
+        assert!(parse_color("00ff00").is_err());
+        assert!(parse_color("#00ff").is_err());
+    }
+
+    #[test]
+    fn test_flipped_tiles_are_solid() {
+        let gid = 3 | 0x8000_0000;
+        assert_eq!(gid & !GID_FLAGS_MASK, 3);
+    }
+
+    #[test]
+    fn test_tile_object_center_uses_bottom_left_origin() {
+        let object = TiledObject {
+            id: 1,
+            name: String::new(),
+            class: "coin".to_string(),
+            x: 32.0,
+            y: 64.0,
+            width: 16.0,
+            height: 16.0,
+            gid: Some(5),
+            properties: Vec::new(),
+        };
+        assert_eq!(object.center(100.0), Vec2::new(40.0, 44.0));
+    }
+}
@@ -0,0 +1,274 @@
+//! Tile grids embedded in level files
+//!
+//! A tilemap is a grid of cells referencing a small tileset. Each tile type
+//! carries gameplay properties (solid, one-way, hazard, ladder) and how it is
+//! drawn, either a cell of a texture atlas or a flat color.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::grid::greedy_mesh;
+use super::{check_position, check_size, LevelValidationError, PlatformData};
+
+/// A grid of tiles placed in the level
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TileMapData {
+    /// World position of the grid's bottom-left corner
+    pub origin: Vec2,
+    /// Size of a single tile in world units
+    pub tile_size: Vec2,
+    pub columns: u32,
+    pub rows: u32,
+    /// Texture atlas the tiles are drawn from, if any
+    #[serde(default)]
+    pub atlas: Option<TileAtlasData>,
+    /// Tile types referenced by `cells`
+    pub tiles: Vec<TileData>,
+    /// Row-major cells with the top row first. `0` is empty and `n` is
+    /// `tiles[n - 1]`.
+    pub cells: Vec<u32>,
+}
+
+/// A texture atlas laid out as a grid of equally sized tiles
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TileAtlasData {
+    /// Asset path of the atlas image
+    pub image: String,
+    /// Size of a single tile in the image, in pixels
+    pub tile_size: UVec2,
+    pub columns: u32,
+    pub rows: u32,
+}
+
+/// A tile type and its gameplay properties
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TileData {
+    /// Blocks movement like a platform
+    #[serde(default)]
+    pub solid: bool,
+    /// Can be jumped through from below and dropped through
+    #[serde(default)]
+    pub one_way: bool,
+    /// Damage per second dealt to the player while touching the tile
+    #[serde(default)]
+    pub hazard: Option<f32>,
+    /// Can be climbed
+    #[serde(default)]
+    pub ladder: bool,
+    /// Index into the atlas, defaults to the tile's position in `tiles`
+    #[serde(default)]
+    pub atlas_index: Option<usize>,
+    /// Flat color used when the tilemap has no atlas
+    #[serde(default)]
+    pub color: Option<[f32; 3]>,
+}
+
+impl TileMapData {
+    /// Returns the tile at a cell, with `y = 0` being the top row
+    pub fn tile(&self, x: u32, y: u32) -> Option<&TileData> {
+        if x >= self.columns || y >= self.rows {
+            return None;
+        }
+        match self.cells.get((y * self.columns + x) as usize) {
+            Some(&index) if index > 0 => self.tiles.get(index as usize - 1),
+            _ => None,
+        }
+    }
+
+    /// World position of the center of a cell
+    pub fn cell_center(&self, x: u32, y: u32) -> Vec2 {
+        self.origin + Vec2::new(x as f32 + 0.5, (self.rows - y) as f32 - 0.5) * self.tile_size
+    }
+
+    /// Returns the non-empty tiles of every cell overlapping `area`
+    pub fn tiles_in(&self, area: Rect) -> impl Iterator<Item = &TileData> {
+        // Cell coordinates counted from the bottom-left corner
+        let min = ((area.min - self.origin) / self.tile_size).floor();
+        let max = ((area.max - self.origin) / self.tile_size).ceil();
+        let columns = min.x.max(0.0) as u32..(max.x.max(0.0) as u32).min(self.columns);
+        let rows = min.y.max(0.0) as u32..(max.y.max(0.0) as u32).min(self.rows);
+
+        rows.flat_map(move |row| {
+            columns
+                .clone()
+                .filter_map(move |x| self.tile(x, self.rows - 1 - row))
+        })
+    }
+
+    /// Solid tiles merged into as few platforms as possible
+    pub fn solid_platforms(&self) -> Vec<PlatformData> {
+        self.merged(|tile| tile.solid && !tile.one_way)
+    }
+
+    /// One-way tiles merged into as few platforms as possible
+    pub fn one_way_platforms(&self) -> Vec<PlatformData> {
+        self.merged(|tile| tile.one_way)
+    }
+
+    /// Ladder tiles merged into as few climbable areas as possible
+    pub fn ladders(&self) -> Vec<PlatformData> {
+        self.merged(|tile| tile.ladder)
+    }
+
+    fn merged(&self, filter: impl Fn(&TileData) -> bool) -> Vec<PlatformData> {
+        greedy_mesh(self.columns, self.rows, |x, y| {
+            self.tile(x, y).is_some_and(&filter)
+        })
+        .iter()
+        .map(|rect| rect.to_platform(self.tile_size, self.rows, self.origin))
+        .collect()
+    }
+
+    /// Checks the grid dimensions and tile references
+    pub(super) fn validate(&self) -> Result<(), LevelValidationError> {
+        check_position("tilemap.origin", self.origin)?;
+        check_size("tilemap.tile_size", self.tile_size)?;
+
+        let expected = (self.columns * self.rows) as usize;
+        if self.cells.len() != expected {
+            return Err(LevelValidationError::new(
+                "tilemap.cells",
+                format!(
+                    "expected {} x {} = {expected} cells, found {}",
+                    self.columns,
+                    self.rows,
+                    self.cells.len()
+                ),
+            ));
+        }
+        if let Some(i) = self
+            .cells
+            .iter()
+            .position(|&index| index as usize > self.tiles.len())
+        {
+            return Err(LevelValidationError::new(
+                format!("tilemap.cells[{i}]"),
+                format!(
+                    "refers to tile {} but only {} tiles are defined",
+                    self.cells[i],
+                    self.tiles.len()
+                ),
+            ));
+        }
+
+        for (i, tile) in self.tiles.iter().enumerate() {
+            if let Some(damage) = tile.hazard {
+                if !(damage.is_finite() && damage >= 0.0) {
+                    return Err(LevelValidationError::new(
+                        format!("tilemap.tiles[{i}].hazard"),
+                        format!("must not be negative, got {damage}"),
+                    ));
+                }
+            }
+        }
+
+        if let Some(atlas) = &self.atlas {
+            if atlas.columns == 0 || atlas.rows == 0 || atlas.tile_size.min_element() == 0 {
+                return Err(LevelValidationError::new(
+                    "tilemap.atlas",
+                    "tile size, columns and rows must be positive",
+                ));
+            }
+            let capacity = (atlas.columns * atlas.rows) as usize;
+            for (i, tile) in self.tiles.iter().enumerate() {
+                let index = tile.atlas_index.unwrap_or(i);
+                if index >= capacity {
+                    return Err(LevelValidationError::new(
+                        format!("tilemap.tiles[{i}].atlas_index"),
+                        format!("{index} is outside the {capacity} tile atlas"),
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 4 x 3 map with a ladder, a one-way ledge and a spiked floor:
+    ///
+    /// ```text
+    /// H...
+    /// H.==
+    /// ##^^
+    /// ```
+    fn sample_map() -> TileMapData {
+        TileMapData {
+            origin: Vec2::new(-32.0, -24.0),
+            tile_size: Vec2::splat(16.0),
+            columns: 4,
+            rows: 3,
+            atlas: None,
+            tiles: vec![
+                TileData {
+                    solid: true,
+                    ..default()
+                },
+                TileData {
+                    one_way: true,
+                    ..default()
+                },
+                TileData {
+                    solid: true,
+                    hazard: Some(30.0),
+                    ..default()
+                },
+                TileData {
+                    ladder: true,
+                    ..default()
+                },
+            ],
+            cells: vec![4, 0, 0, 0, 4, 0, 2, 2, 1, 1, 3, 3],
+        }
+    }
+
+    #[test]
+    fn test_tilemap_merges_colliders_by_property() {
+        let map = sample_map();
+        assert!(map.validate().is_ok());
+
+        // Plain and spiked floor tiles are both solid, so they merge
+        let solid = map.solid_platforms();
+        assert_eq!(solid.len(), 1);
+        assert_eq!(solid[0].size, Vec2::new(64.0, 16.0));
+        assert_eq!(solid[0].position, Vec2::new(0.0, -16.0));
+
+        let one_way = map.one_way_platforms();
+        assert_eq!(one_way.len(), 1);
+        assert_eq!(one_way[0].position, Vec2::new(16.0, 0.0));
+
+        // The two ladder tiles make one climbable column
+        let ladders = map.ladders();
+        assert_eq!(ladders.len(), 1);
+        assert_eq!(ladders[0].size, Vec2::new(16.0, 32.0));
+    }
+
+    #[test]
+    fn test_tilemap_tiles_in_area() {
+        let map = sample_map();
+        assert_eq!(map.cell_center(0, 0), Vec2::new(-24.0, 16.0));
+
+        // Resting exactly on top of the spikes does not overlap them
+        let feet = Rect::new(8.0, -8.0, 24.0, 8.0);
+        assert!(map.tiles_in(feet).all(|tile| tile.hazard.is_none()));
+        let feet = Rect::new(8.0, -9.0, 24.0, 7.0);
+        assert!(map.tiles_in(feet).any(|tile| tile.hazard.is_some()));
+
+        let ladder_area = Rect::new(-30.0, 0.0, -20.0, 20.0);
+        assert!(map.tiles_in(ladder_area).all(|tile| tile.ladder));
+        assert_eq!(map.tiles_in(ladder_area).count(), 2);
+    }
+
+    #[test]
+    fn test_tilemap_validate_rejects_unknown_tile() {
+        let mut map = sample_map();
+        map.cells[5] = 9;
+
+        let error = map.validate().unwrap_err();
+        assert_eq!(error.entry, "tilemap.cells[5]");
+    }
+}
@@ -2,16 +2,19 @@
 //!
 //! A Bevy game template designed for indie game developers.
 //! This template provides a solid foundation with organized modules
-//! for components, systems, resources, states, and plugins.
+//! for components, events, systems, resources, states, and plugins.
 
 pub mod components;
+pub mod events;
 pub mod game;
+pub mod level;
 pub mod plugins;
 pub mod resources;
 pub mod states;
 pub mod systems;
 
 pub use components::*;
+pub use events::*;
 pub use game::*;
 pub use resources::*;
 pub use states::*;
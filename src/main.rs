@@ -1,16 +1,42 @@
+use std::process::ExitCode;
+
 use bevy::prelude::*;
+use template_bevy::game::cli::{CliArgs, USAGE};
+use template_bevy::game::config::GameConfig;
 use template_bevy::plugins::GamePlugin;
+use template_bevy::resources::ConfigFile;
+
+fn main() -> ExitCode {
+    let args = match CliArgs::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(error) => {
+            eprintln!("error: {error}\n\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+    if args.help {
+        println!("{USAGE}");
+        return ExitCode::SUCCESS;
+    }
+
+    let config_file = ConfigFile::from_args(&args);
+    let config = match GameConfig::load(&config_file.path, &config_file.overrides) {
+        Ok(config) => config,
+        Err(error) => {
+            eprintln!("error: {}: {error}", config_file.path);
+            return ExitCode::FAILURE;
+        }
+    };
 
-fn main() {
     App::new()
         .add_plugins(DefaultPlugins.set(WindowPlugin {
-            primary_window: Some(Window {
-                title: "2D Mario-Style Platformer".to_string(),
-                resolution: (1280.0, 720.0).into(),
-                ..default()
-            }),
+            primary_window: Some(config.window.to_window()),
             ..default()
         }))
+        .insert_resource(config)
+        .insert_resource(config_file)
         .add_plugins(GamePlugin)
         .run();
+
+    ExitCode::SUCCESS
 }
@@ -5,13 +5,30 @@
 
 use bevy::prelude::*;
 
-use crate::resources::{GameSettings, GameTimer, ObstacleSpawnTimer, Score};
+use crate::components::Obstacle;
+use crate::events::{Collision, Crushed, TriggerEntered, TriggerExited};
+use crate::game::config::{GameConfig, GameConfigLoader};
+use crate::level::{LdtkProjectLoader, LevelAssetLoader, LevelData, TiledMapLoader};
+use crate::resources::{
+    ActiveLevel, CameraBounds, CameraView, ConfigFile, Difficulty, EntityPool, GameRng,
+    GameSettings, GameTimer, LevelList, LevelResults, ObstacleSpawnTimer, Score, SpatialHash,
+    TileGrid, WaveState,
+};
 use crate::states::GameState;
 use crate::systems::{
-    apply_auto_movement, apply_gravity, apply_velocity, camera_follow_system,
-    check_obstacle_collisions, check_platform_collisions, despawn_offscreen_obstacles, player_jump,
-    player_movement, setup_camera, setup_camera_follow, spawn_game_ui, spawn_obstacles,
-    spawn_platforms, spawn_player, update_health_bar, update_score_display,
+    advance_level, apply_auto_movement, apply_config, apply_config_to_entities, apply_crush_damage,
+    apply_gravity, apply_orbits, apply_sine_bob, apply_swim_physics, apply_velocity,
+    bounce_obstacles, camera_follow_system, check_collectible_pickups, check_enemy_collisions,
+    check_kill_zones, check_level_bounds, check_level_goal, check_level_transition,
+    check_levels_loaded, check_obstacle_collisions, check_platform_collisions, check_tile_hazards,
+    despawn_out_of_bounds, despawn_results_screen, detect_collisions, detect_crushing,
+    follow_spline_paths, load_config, load_current_level, load_levels, patrol_enemies,
+    player_climb, player_drop_through, player_jump, player_movement, push_bodies,
+    register_dynamic_bodies, reload_levels, setup_camera, setup_camera_follow, spawn_game_ui,
+    spawn_obstacles, spawn_player, spawn_results_screen, steer_homing, stream_levels,
+    tick_game_timer, turn_colliding_enemies, update_breath, update_camera_view, update_difficulty,
+    update_health_bar, update_pressure_plates, update_score_display, update_spatial_hash,
+    update_submerged, update_triggers,
 };
 
 /// Main game plugin that sets up all game systems
@@ -22,15 +39,77 @@ impl Plugin for GamePlugin {
         app
             // Initialize states
             .init_state::<GameState>()
+            // Register level assets
+            .init_asset::<LevelData>()
+            .init_asset_loader::<LevelAssetLoader>()
+            .init_asset_loader::<TiledMapLoader>()
+            .init_asset_loader::<LdtkProjectLoader>()
+            // Register config assets
+            .init_asset::<GameConfig>()
+            .init_asset_loader::<GameConfigLoader>()
+            // Register events
+            .add_event::<Collision>()
+            .add_event::<TriggerEntered>()
+            .add_event::<TriggerExited>()
+            .add_event::<Crushed>()
             // Initialize resources
             .init_resource::<GameSettings>()
             .init_resource::<Score>()
             .init_resource::<GameTimer>()
             .init_resource::<ObstacleSpawnTimer>()
+            .init_resource::<Difficulty>()
+            .init_resource::<WaveState>()
+            .init_resource::<LevelList>()
+            .init_resource::<LevelResults>()
+            .init_resource::<ActiveLevel>()
+            .init_resource::<CameraBounds>()
+            .init_resource::<CameraView>()
+            .init_resource::<EntityPool<Obstacle>>()
+            .init_resource::<SpatialHash>()
+            .init_resource::<TileGrid>()
+            .init_resource::<GameConfig>()
+            .init_resource::<ConfigFile>()
+            // Seeded from the config, so it must come after it
+            .init_resource::<GameRng>()
             // Setup systems (run once on startup)
             .add_systems(
                 Startup,
-                (setup_camera, spawn_player, spawn_platforms, spawn_game_ui),
+                (
+                    setup_camera,
+                    spawn_player,
+                    spawn_game_ui,
+                    load_levels,
+                    load_config,
+                ),
+            )
+            // Apply the config file whenever it loads or changes on disk
+            .add_systems(
+                Update,
+                (
+                    apply_config,
+                    apply_config_to_entities.run_if(resource_changed::<GameConfig>),
+                )
+                    .chain(),
+            )
+            // Wait for level files to load before starting to play
+            .add_systems(
+                Update,
+                check_levels_loaded.run_if(in_state(GameState::Loading)),
+            )
+            // Spawn the first level, and the next one after each results screen
+            .add_systems(
+                OnTransition {
+                    exited: GameState::Loading,
+                    entered: GameState::Playing,
+                },
+                load_current_level,
+            )
+            .add_systems(
+                OnTransition {
+                    exited: GameState::LevelComplete,
+                    entered: GameState::Playing,
+                },
+                load_current_level,
             )
             // Post-startup setup for camera follow (after player is spawned)
             .add_systems(OnEnter(GameState::Playing), setup_camera_follow)
@@ -39,19 +118,64 @@ impl Plugin for GamePlugin {
             .add_systems(
                 Update,
                 (
+                    // Level timer, hot reloading and streaming
+                    tick_game_timer,
+                    reload_levels,
+                    stream_levels,
+                    // Enter and leave water before input reads the movement mode
+                    update_submerged,
                     // Player input systems
                     player_movement,
                     player_jump,
+                    player_drop_through,
+                    player_climb,
                     // Physics systems
+                    register_dynamic_bodies,
                     apply_gravity,
+                    apply_swim_physics,
                     apply_velocity,
-                    apply_auto_movement,
-                    // Collision systems
-                    check_platform_collisions,
-                    check_obstacle_collisions,
-                    // Obstacle spawning and cleanup
-                    spawn_obstacles,
-                    despawn_offscreen_obstacles,
+                    // Obstacle and enemy movement
+                    (
+                        steer_homing,
+                        apply_auto_movement,
+                        apply_sine_bob,
+                        apply_orbits,
+                        follow_spline_paths,
+                        bounce_obstacles,
+                        patrol_enemies,
+                    )
+                        .chain(),
+                    // Collision systems, after the broad-phase grid catches up.
+                    // Responses read the pairs found by `detect_collisions`.
+                    (
+                        update_spatial_hash,
+                        check_platform_collisions,
+                        push_bodies,
+                        detect_crushing,
+                        apply_crush_damage,
+                        detect_collisions,
+                        update_triggers,
+                        update_pressure_plates,
+                        check_obstacle_collisions,
+                        check_enemy_collisions,
+                        turn_colliding_enemies,
+                        check_tile_hazards,
+                        update_breath,
+                        check_collectible_pickups,
+                        check_level_goal,
+                        check_level_transition,
+                        check_kill_zones,
+                        check_level_bounds,
+                    )
+                        .chain(),
+                    // Difficulty, obstacle spawning and cleanup
+                    (
+                        update_camera_view,
+                        update_difficulty,
+                        spawn_obstacles,
+                        despawn_out_of_bounds,
+                    )
+                        .chain(),
                     // Camera system
                     camera_follow_system,
                     // UI systems
@@ -61,18 +185,16 @@ impl Plugin for GamePlugin {
                     .chain()
                     .run_if(in_state(GameState::Playing)),
             )
-            // Transition from Loading to MainMenu after startup
-            .add_systems(OnEnter(GameState::Loading), transition_to_menu);
+            // Level completion flow
+            .add_systems(OnEnter(GameState::LevelComplete), spawn_results_screen)
+            .add_systems(
+                Update,
+                advance_level.run_if(in_state(GameState::LevelComplete)),
+            )
+            .add_systems(OnExit(GameState::LevelComplete), despawn_results_screen);
     }
 }
 
-/// System to transition from Loading to MainMenu
-fn transition_to_menu(mut next_state: ResMut<NextState<GameState>>) {
-    // In a real game, you would wait for assets to load
-    // For now, transition immediately to Playing for demo purposes
-    next_state.set(GameState::Playing);
-}
-
 /// Debug plugin for development
 pub struct DebugPlugin;
 
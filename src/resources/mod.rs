@@ -3,9 +3,23 @@
 //! This module contains all game resources (global state).
 //! Resources are unique data that exists independently of entities.
 
+use std::marker::PhantomData;
+
+use bevy::ecs::entity::EntityHashMap;
+use bevy::ecs::system::EntityCommands;
 use bevy::prelude::*;
+use bevy::utils::HashMap;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use serde::{Deserialize, Serialize};
 
+use crate::components::{Health, Pooled};
+use crate::game::cli::CliArgs;
+use crate::game::config::{ConfigOverride, GameConfig, ScoringConfig, ScreenEdge};
+use crate::game::waves::{WaveConfig, WaveOrder};
+use crate::level::{LevelData, TileMapData};
+
 /// Game settings resource
 #[derive(Resource, Debug, Clone, Serialize, Deserialize)]
 pub struct GameSettings {
@@ -80,6 +94,196 @@ impl GameTimer {
     pub fn reset(&mut self) {
         self.elapsed = 0.0;
     }
+
+    /// Returns the time left before `limit` seconds have elapsed
+    pub fn remaining(&self, limit: f32) -> f32 {
+        (limit - self.elapsed).max(0.0)
+    }
+}
+
+/// Recycles sprite entities marked with `T` instead of despawning them.
+///
+/// Released entities keep only their sprite components, are hidden and are
+/// marked [`Pooled`], so no gameplay query matches them until they are
+/// acquired and given their components again.
+#[derive(Resource, Debug)]
+pub struct EntityPool<T> {
+    free: Vec<Entity>,
+    /// Maximum number of entities kept, extra entities are despawned
+    pub capacity: usize,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for EntityPool<T> {
+    fn default() -> Self {
+        Self::new(256)
+    }
+}
+
+impl<T> EntityPool<T> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            free: Vec::new(),
+            capacity,
+            marker: PhantomData,
+        }
+    }
+
+    /// Number of entities ready to be reused
+    pub fn available(&self) -> usize {
+        self.free.len()
+    }
+
+    /// Returns a pooled entity, or spawns a new one when the pool is empty.
+    /// The caller inserts the entity's components, including a visible
+    /// sprite bundle.
+    pub fn acquire<'a>(&mut self, commands: &'a mut Commands) -> EntityCommands<'a> {
+        match self.free.pop() {
+            Some(entity) => {
+                let mut entity = commands.entity(entity);
+                entity.remove::<Pooled>();
+                entity
+            }
+            None => commands.spawn_empty(),
+        }
+    }
+
+    /// Hides `entity` and keeps it for reuse, or despawns it if the pool is full
+    pub fn release(&mut self, commands: &mut Commands, entity: Entity) {
+        if self.free.len() >= self.capacity {
+            commands.entity(entity).despawn_recursive();
+            return;
+        }
+        commands
+            .entity(entity)
+            .retain::<SpriteBundle>()
+            .insert((Pooled, Visibility::Hidden));
+        self.free.push(entity);
+    }
+}
+
+/// Broad-phase grid of every entity with a
+/// [`BoxCollider`](crate::components::BoxCollider).
+///
+/// Each entity is stored in every cell its bounds overlap. Collision systems
+/// ask for the entities near an area instead of testing every collider, then
+/// run their exact checks on those candidates only. Entries are updated by
+/// `update_spatial_hash` when colliders move, change or are removed.
+#[derive(Resource, Debug, Clone)]
+pub struct SpatialHash {
+    cell_size: f32,
+    cells: HashMap<IVec2, Vec<Entity>>,
+    /// Range of cells each entity is stored in
+    spans: EntityHashMap<(IVec2, IVec2)>,
+}
+
+impl Default for SpatialHash {
+    fn default() -> Self {
+        Self::new(128.0)
+    }
+}
+
+impl SpatialHash {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            cells: HashMap::default(),
+            spans: EntityHashMap::default(),
+        }
+    }
+
+    /// Number of entities in the grid
+    pub fn len(&self) -> usize {
+        self.spans.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+
+    /// Range of cells overlapped by `area`
+    fn span(&self, area: Rect) -> (IVec2, IVec2) {
+        (
+            (area.min / self.cell_size).floor().as_ivec2(),
+            (area.max / self.cell_size).floor().as_ivec2(),
+        )
+    }
+
+    /// Stores `entity` with the given bounds, replacing any previous entry
+    pub fn insert(&mut self, entity: Entity, bounds: Rect) {
+        let span = self.span(bounds);
+        if self.spans.get(&entity) == Some(&span) {
+            return;
+        }
+        self.remove(entity);
+
+        for y in span.0.y..=span.1.y {
+            for x in span.0.x..=span.1.x {
+                self.cells.entry(IVec2::new(x, y)).or_default().push(entity);
+            }
+        }
+        self.spans.insert(entity, span);
+    }
+
+    /// Removes `entity` from the grid
+    pub fn remove(&mut self, entity: Entity) {
+        let Some((min, max)) = self.spans.remove(&entity) else {
+            return;
+        };
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                let cell = IVec2::new(x, y);
+                let Some(entities) = self.cells.get_mut(&cell) else {
+                    continue;
+                };
+                if let Some(i) = entities.iter().position(|&other| other == entity) {
+                    entities.swap_remove(i);
+                }
+                if entities.is_empty() {
+                    self.cells.remove(&cell);
+                }
+            }
+        }
+    }
+
+    /// Entities stored in the cells overlapping `area`, each listed once.
+    /// These are candidates only, their bounds may not overlap `area`.
+    pub fn query(&self, area: Rect) -> Vec<Entity> {
+        let (min, max) = self.span(area);
+        let mut found = Vec::new();
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                if let Some(entities) = self.cells.get(&IVec2::new(x, y)) {
+                    found.extend_from_slice(entities);
+                }
+            }
+        }
+        found.sort_unstable();
+        found.dedup();
+        found
+    }
+}
+
+/// Multipliers applied to obstacle spawning, updated from the difficulty
+/// curves as the game timer runs
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct Difficulty {
+    /// Multiplies the spawn rate, so `2.0` halves the spawn interval
+    pub spawn_rate: f32,
+    pub speed: f32,
+    pub size: f32,
+    pub damage: f32,
+}
+
+impl Default for Difficulty {
+    fn default() -> Self {
+        Self {
+            spawn_rate: 1.0,
+            speed: 1.0,
+            size: 1.0,
+            damage: 1.0,
+        }
+    }
 }
 
 /// Obstacle spawn timer resource
@@ -104,6 +308,354 @@ impl ObstacleSpawnTimer {
     }
 }
 
+/// Playback position in the configured obstacle waves
+#[derive(Resource, Debug, Clone, Default)]
+pub struct WaveState {
+    /// Index of the wave being played
+    pub current: Option<usize>,
+    /// Index of the next entry to spawn in the current wave
+    pub next_entry: usize,
+    /// Seconds until the next entry spawns, or until the wave's rest ends
+    pub countdown: f32,
+    /// Wave started next with [`WaveOrder::InOrder`]
+    pub next_in_order: usize,
+}
+
+impl WaveState {
+    /// Advances playback by `delta` seconds and returns the
+    /// `(wave, entry)` indices that are due, in order
+    pub fn advance(
+        &mut self,
+        delta: f32,
+        config: &WaveConfig,
+        rng: &mut impl Rng,
+    ) -> Vec<(usize, usize)> {
+        let mut due = Vec::new();
+        if config.waves.is_empty() {
+            return due;
+        }
+        self.countdown -= delta;
+
+        loop {
+            let Some(wave_index) = self.current.filter(|&i| i < config.waves.len()) else {
+                let wave_index = self.pick_wave(config, rng);
+                self.current = Some(wave_index);
+                self.next_entry = 0;
+                self.countdown += config.waves[wave_index].entries[0].delay;
+                continue;
+            };
+            let wave = &config.waves[wave_index];
+
+            if self.countdown > 0.0 {
+                break;
+            }
+            if self.next_entry >= wave.entries.len() {
+                // Rest is over
+                self.current = None;
+                continue;
+            }
+
+            due.push((wave_index, self.next_entry));
+            self.next_entry += 1;
+            self.countdown += match wave.entries.get(self.next_entry) {
+                Some(entry) => entry.delay,
+                None => wave.rest,
+            };
+        }
+
+        due
+    }
+
+    fn pick_wave(&mut self, config: &WaveConfig, rng: &mut impl Rng) -> usize {
+        match config.order {
+            WaveOrder::InOrder => {
+                let index = self.next_in_order % config.waves.len();
+                self.next_in_order = index + 1;
+                index
+            }
+            WaveOrder::WeightedRandom => {
+                WeightedIndex::new(config.waves.iter().map(|wave| wave.weight))
+                    .map_or(0, |weights| weights.sample(rng))
+            }
+        }
+    }
+}
+
+/// Ordered list of level asset paths and the index of the level being played
+#[derive(Resource, Debug, Clone)]
+pub struct LevelList {
+    /// Asset paths of the levels, in play order
+    pub levels: Vec<String>,
+    /// Handles to the loaded levels, parallel to `levels`
+    pub handles: Vec<Handle<LevelData>>,
+    pub current: usize,
+}
+
+impl Default for LevelList {
+    fn default() -> Self {
+        Self::new(["levels/level_01.level.ron", "levels/level_02.level.ron"])
+    }
+}
+
+impl LevelList {
+    pub fn new<I, S>(levels: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            levels: levels.into_iter().map(Into::into).collect(),
+            handles: Vec::new(),
+            current: 0,
+        }
+    }
+
+    /// Returns the asset path of the level currently being played
+    pub fn current_path(&self) -> Option<&str> {
+        self.levels.get(self.current).map(String::as_str)
+    }
+
+    /// Returns the handle of the level currently being played
+    pub fn current_handle(&self) -> Option<&Handle<LevelData>> {
+        self.handles.get(self.current)
+    }
+
+    /// Returns true if there is a level after the current one
+    pub fn has_next(&self) -> bool {
+        self.current + 1 < self.levels.len()
+    }
+
+    /// Moves to the next level, returning false if the list is exhausted
+    pub fn advance(&mut self) -> bool {
+        if self.has_next() {
+            self.current += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.current = 0;
+    }
+
+    /// Drops the level at `index`, such as one that failed to load. The
+    /// current level stays the same, or becomes the one after it when it is
+    /// the one dropped.
+    pub fn remove(&mut self, index: usize) {
+        if index >= self.levels.len() {
+            return;
+        }
+        self.levels.remove(index);
+        if index < self.handles.len() {
+            self.handles.remove(index);
+        }
+        if index < self.current {
+            self.current -= 1;
+        }
+        self.current = self.current.min(self.levels.len().saturating_sub(1));
+    }
+}
+
+/// The level the player is currently in, and the neighbouring levels
+/// streamed in around it
+#[derive(Resource, Debug, Clone, Default)]
+pub struct ActiveLevel {
+    pub handle: Handle<LevelData>,
+    pub neighbours: Vec<Handle<LevelData>>,
+}
+
+impl ActiveLevel {
+    /// Returns the IDs of the active level and its neighbours
+    pub fn ids(&self) -> impl Iterator<Item = AssetId<LevelData>> + '_ {
+        std::iter::once(self.handle.id()).chain(self.neighbours.iter().map(Handle::id))
+    }
+}
+
+/// Region the camera is kept within, set from the current level
+#[derive(Resource, Debug, Clone, Default)]
+pub struct CameraBounds(pub Option<Rect>);
+
+/// World-space area seen by the main camera, used to place spawned obstacles
+/// just off screen and to despawn them once they leave
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct CameraView(pub Rect);
+
+impl Default for CameraView {
+    /// The default window centered on the origin, until the camera updates it
+    fn default() -> Self {
+        Self(Rect::from_center_size(Vec2::ZERO, Vec2::new(1280.0, 720.0)))
+    }
+}
+
+impl CameraView {
+    /// A point `margin` outside `edge`, `along` the edge from the view center
+    pub fn spawn_point(&self, edge: ScreenEdge, margin: f32, along: f32) -> Vec2 {
+        let center = self.0.center();
+        match edge {
+            ScreenEdge::Left => Vec2::new(self.0.min.x - margin, center.y + along),
+            ScreenEdge::Right => Vec2::new(self.0.max.x + margin, center.y + along),
+            ScreenEdge::Top => Vec2::new(center.x + along, self.0.max.y + margin),
+            ScreenEdge::Bottom => Vec2::new(center.x + along, self.0.min.y - margin),
+        }
+    }
+
+    /// Whether `position` is more than `margin` outside the view and, when a
+    /// `direction` is given, moving further away
+    pub fn has_left(&self, position: Vec2, direction: Option<Vec2>, margin: f32) -> bool {
+        let area = self.0.inflate(margin);
+        if area.contains(position) {
+            return false;
+        }
+        direction.is_none_or(|direction| (position - area.center()).dot(direction) > 0.0)
+    }
+}
+
+/// Tile grid of the current level, used for tile property lookups
+#[derive(Resource, Debug, Clone, Default)]
+pub struct TileGrid(pub Option<TileMapData>);
+
+/// The config file applied to the [`GameConfig`] resource whenever it loads or
+/// changes on disk, with the command line overrides applied on top
+#[derive(Resource, Debug, Clone)]
+pub struct ConfigFile {
+    pub path: String,
+    pub overrides: Vec<ConfigOverride>,
+    pub handle: Handle<GameConfig>,
+}
+
+impl Default for ConfigFile {
+    fn default() -> Self {
+        Self::new("game.config.ron")
+    }
+}
+
+impl ConfigFile {
+    /// Uses the config file and overrides given on the command line
+    pub fn from_args(args: &CliArgs) -> Self {
+        let mut file = args.config.clone().map_or_else(Self::default, Self::new);
+        file.overrides = args.overrides.clone();
+        if let Some(seed) = args.seed {
+            file.overrides.push(ConfigOverride {
+                key: "seed".to_string(),
+                value: seed.to_string(),
+            });
+        }
+        file
+    }
+
+    pub fn new(path: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            overrides: Vec::new(),
+            handle: Handle::default(),
+        }
+    }
+}
+
+/// Independent random number streams, so adding randomness to one
+/// subsystem never changes the sequence another one sees
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RngStream {
+    /// Obstacle positions, sizes and speeds
+    Obstacles,
+    /// Which wave plays next
+    Waves,
+}
+
+impl RngStream {
+    const ALL: [RngStream; 2] = [RngStream::Obstacles, RngStream::Waves];
+}
+
+/// Seeded random number generator for all gameplay randomness. The same
+/// seed always produces the same obstacle sequence.
+#[derive(Resource, Debug, Clone)]
+pub struct GameRng {
+    seed: u64,
+    streams: Vec<StdRng>,
+}
+
+impl GameRng {
+    pub fn from_seed(seed: u64) -> Self {
+        Self {
+            seed,
+            streams: RngStream::ALL
+                .iter()
+                .map(|&stream| {
+                    // Spread the streams apart so neighbouring seeds don't overlap
+                    let salt = (stream as u64 + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+                    StdRng::seed_from_u64(seed ^ salt)
+                })
+                .collect(),
+        }
+    }
+
+    /// Creates a generator with a random seed
+    pub fn from_entropy() -> Self {
+        Self::from_seed(rand::random())
+    }
+
+    /// Seed the generator was created with, for reproducing a run
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Returns the generator for a subsystem
+    pub fn stream(&mut self, stream: RngStream) -> &mut StdRng {
+        &mut self.streams[stream as usize]
+    }
+}
+
+impl FromWorld for GameRng {
+    /// Seeds from [`GameConfig::seed`], or randomly when it is unset
+    fn from_world(world: &mut World) -> Self {
+        let rng = match world
+            .get_resource::<GameConfig>()
+            .and_then(|config| config.seed)
+        {
+            Some(seed) => Self::from_seed(seed),
+            None => Self::from_entropy(),
+        };
+        info!("Game seed: {}", rng.seed());
+        rng
+    }
+}
+
+/// Results of the last completed level, shown on the results screen
+#[derive(Resource, Debug, Clone, Default)]
+pub struct LevelResults {
+    pub level_name: String,
+    pub time_remaining: f32,
+    pub health_remaining: f32,
+    pub time_bonus: u32,
+    pub health_bonus: u32,
+}
+
+impl LevelResults {
+    /// Tallies the completion bonus from the remaining time and health
+    pub fn tally(
+        level: &LevelData,
+        timer: &GameTimer,
+        health: &Health,
+        scoring: &ScoringConfig,
+    ) -> Self {
+        let time_remaining = timer.remaining(level.time_limit);
+        let health_remaining = health.current.max(0.0);
+
+        Self {
+            level_name: level.name.clone(),
+            time_remaining,
+            health_remaining,
+            time_bonus: time_remaining.floor() as u32 * scoring.time_bonus_per_second,
+            health_bonus: health_remaining.round() as u32 * scoring.health_bonus_per_point,
+        }
+    }
+
+    pub fn total_bonus(&self) -> u32 {
+        self.time_bonus + self.health_bonus
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -141,4 +693,138 @@ mod tests {
         timer.tick(1.0);
         assert!((timer.elapsed - 1.0).abs() < f32::EPSILON);
     }
+
+    #[test]
+    fn test_game_timer_remaining() {
+        let mut timer = GameTimer::default();
+        timer.tick(30.0);
+        assert!((timer.remaining(100.0) - 70.0).abs() < f32::EPSILON);
+        timer.tick(100.0);
+        assert_eq!(timer.remaining(100.0), 0.0);
+    }
+
+    #[test]
+    fn test_spatial_hash_query_and_update() {
+        let mut hash = SpatialHash::new(100.0);
+        let a = Entity::from_raw(1);
+        let b = Entity::from_raw(2);
+
+        // `a` spans four cells but is only reported once
+        hash.insert(a, Rect::new(50.0, 50.0, 150.0, 150.0));
+        hash.insert(b, Rect::new(500.0, 0.0, 520.0, 20.0));
+        assert_eq!(hash.len(), 2);
+        assert_eq!(hash.query(Rect::new(0.0, 0.0, 250.0, 250.0)), vec![a]);
+        assert_eq!(
+            hash.query(Rect::new(-300.0, -300.0, -200.0, -200.0)),
+            vec![]
+        );
+
+        // Moving `b` next to `a` makes both candidates
+        hash.insert(b, Rect::new(160.0, 160.0, 180.0, 180.0));
+        assert_eq!(
+            hash.query(Rect::new(120.0, 120.0, 130.0, 130.0)),
+            vec![a, b]
+        );
+        assert!(hash.query(Rect::new(500.0, 0.0, 510.0, 10.0)).is_empty());
+
+        hash.remove(a);
+        assert_eq!(hash.query(Rect::new(0.0, 0.0, 250.0, 250.0)), vec![b]);
+        assert_eq!(hash.len(), 1);
+    }
+
+    #[test]
+    fn test_camera_view_spawn_and_leave() {
+        let view = CameraView(Rect::from_center_size(
+            Vec2::new(1000.0, 0.0),
+            Vec2::splat(200.0),
+        ));
+        assert_eq!(
+            view.spawn_point(ScreenEdge::Right, 50.0, 20.0),
+            Vec2::new(1150.0, 20.0)
+        );
+        assert_eq!(
+            view.spawn_point(ScreenEdge::Top, 50.0, -30.0),
+            Vec2::new(970.0, 150.0)
+        );
+
+        // Just spawned on the right and moving left, so still arriving
+        let spawned = Vec2::new(1150.0, 0.0);
+        assert!(!view.has_left(spawned, Some(Vec2::NEG_X), 100.0));
+        assert!(view.has_left(Vec2::new(1300.0, 0.0), Some(Vec2::X), 100.0));
+        assert!(!view.has_left(Vec2::new(1300.0, 0.0), Some(Vec2::NEG_X), 100.0));
+        assert!(view.has_left(Vec2::new(700.0, 0.0), None, 100.0));
+    }
+
+    #[test]
+    fn test_game_rng_streams_follow_the_seed() {
+        let mut a = GameRng::from_seed(7);
+        let mut b = GameRng::from_seed(7);
+        let mut c = GameRng::from_seed(8);
+        assert_eq!(b.seed(), 7);
+
+        let first = a.stream(RngStream::Obstacles).gen::<u64>();
+        assert_eq!(first, b.stream(RngStream::Obstacles).gen::<u64>());
+        assert_ne!(first, c.stream(RngStream::Obstacles).gen::<u64>());
+    }
+
+    #[test]
+    fn test_game_rng_streams_are_independent() {
+        let mut a = GameRng::from_seed(7);
+        let mut b = GameRng::from_seed(7);
+
+        // Picking waves must not shift the obstacle stream
+        let _: u64 = a.stream(RngStream::Waves).gen();
+        assert_eq!(
+            a.stream(RngStream::Obstacles).gen::<u64>(),
+            b.stream(RngStream::Obstacles).gen::<u64>()
+        );
+        assert_ne!(
+            b.stream(RngStream::Obstacles).gen::<u64>(),
+            b.stream(RngStream::Waves).gen::<u64>()
+        );
+    }
+
+    #[test]
+    fn test_level_list_advance() {
+        let mut levels = LevelList::new(["one.level.ron", "two.level.ron"]);
+        assert_eq!(levels.current_path(), Some("one.level.ron"));
+        assert!(levels.advance());
+        assert_eq!(levels.current_path(), Some("two.level.ron"));
+        assert!(!levels.has_next());
+        assert!(!levels.advance());
+        levels.reset();
+        assert_eq!(levels.current, 0);
+    }
+
+    #[test]
+    fn test_level_list_remove_skips_to_next_level() {
+        let mut levels = LevelList::new(["one.level.ron", "two.level.ron", "three.level.ron"]);
+        levels.remove(0);
+        assert_eq!(levels.current_path(), Some("two.level.ron"));
+        levels.advance();
+        levels.remove(0);
+        assert_eq!(levels.current_path(), Some("three.level.ron"));
+        assert!(!levels.has_next());
+    }
+
+    #[test]
+    fn test_level_results_tally() {
+        let level = LevelData::from_ron(
+            r#"(name: "Test", time_limit: 100.0, player_spawn: (0.0, 0.0), goal: (position: (0.0, 0.0)))"#,
+        )
+        .unwrap();
+        let mut timer = GameTimer::default();
+        timer.tick(40.5);
+        let mut health = Health::new(100.0);
+        health.take_damage(20.0);
+
+        let scoring = ScoringConfig::default();
+        let results = LevelResults::tally(&level, &timer, &health, &scoring);
+        assert_eq!(results.time_bonus, 59 * scoring.time_bonus_per_second);
+        assert_eq!(results.health_bonus, 80 * scoring.health_bonus_per_point);
+        assert_eq!(
+            results.total_bonus(),
+            results.time_bonus + results.health_bonus
+        );
+    }
 }
@@ -17,6 +17,8 @@ pub enum GameState {
     Playing,
     /// Game is paused
     Paused,
+    /// Level results screen shown after reaching the goal
+    LevelComplete,
     /// Game over screen
     GameOver,
 }
@@ -1,14 +1,202 @@
 //! Auto-movement systems for entities that move automatically
+//!
+//! Entities [`Submerged`] in water move at a fraction of their speed, as if
+//! their time ran slower.
 
 use bevy::prelude::*;
 
-use crate::components::AutoMove;
+use crate::components::{
+    AutoMove, Bounce, BoxCollider, CollisionLayers, Homing, Orbit, Patrol, Platform, Player,
+    SineBob, Slope, SplinePath, Submerged,
+};
+use crate::resources::SpatialHash;
+
+/// Seconds of movement this frame for an entity that may be under water
+fn scaled_delta(time: &Time, submerged: Option<&Submerged>) -> f32 {
+    time.delta_seconds() * submerged.map_or(1.0, Submerged::speed_scale)
+}
 
 /// Applies automatic movement to entities with AutoMove component
-pub fn apply_auto_movement(time: Res<Time>, mut query: Query<(&AutoMove, &mut Transform)>) {
-    for (auto_move, mut transform) in query.iter_mut() {
-        let movement = auto_move.direction * auto_move.speed * time.delta_seconds();
+pub fn apply_auto_movement(
+    time: Res<Time>,
+    mut query: Query<(&AutoMove, &mut Transform, Option<&Submerged>)>,
+) {
+    for (auto_move, mut transform, submerged) in query.iter_mut() {
+        let movement = auto_move.direction * auto_move.speed * scaled_delta(&time, submerged);
         transform.translation.x += movement.x;
         transform.translation.y += movement.y;
     }
 }
+
+/// Turns homing entities toward the player, limited by their turn rate
+pub fn steer_homing(
+    time: Res<Time>,
+    player_query: Query<&Transform, With<Player>>,
+    mut query: Query<(&Homing, &mut AutoMove, &Transform), Without<Player>>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+    let target = player_transform.translation.truncate();
+
+    for (homing, mut auto_move, transform) in query.iter_mut() {
+        let to_target = target - transform.translation.truncate();
+        auto_move.direction = homing.steer(auto_move.direction, to_target, time.delta_seconds());
+    }
+}
+
+/// Offsets bobbing entities along their sine wave
+pub fn apply_sine_bob(
+    time: Res<Time>,
+    mut query: Query<(&mut SineBob, &mut Transform, Option<&Submerged>)>,
+) {
+    for (mut bob, mut transform, submerged) in query.iter_mut() {
+        let elapsed = bob.elapsed + scaled_delta(&time, submerged);
+        transform.translation.y += bob.offset(elapsed) - bob.offset(bob.elapsed);
+        bob.elapsed = elapsed;
+    }
+}
+
+/// Moves orbiting entities around their center
+pub fn apply_orbits(
+    time: Res<Time>,
+    mut query: Query<(&mut Orbit, &mut Transform, Option<&Submerged>)>,
+) {
+    for (mut orbit, mut transform, submerged) in query.iter_mut() {
+        let angle = orbit.angle + orbit.angular_speed * scaled_delta(&time, submerged);
+        let movement = orbit.offset(angle) - orbit.offset(orbit.angle);
+        transform.translation += movement.extend(0.0);
+        orbit.angle = angle % std::f32::consts::TAU;
+    }
+}
+
+/// Moves entities along their spline paths. At the end of a path that
+/// doesn't loop, entities keep moving in the direction it ended in.
+pub fn follow_spline_paths(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut SplinePath, &mut Transform, Option<&Submerged>)>,
+) {
+    for (entity, mut path, mut transform, submerged) in query.iter_mut() {
+        let segments = path.segments();
+        if segments == 0 || path.progress >= segments as f32 {
+            continue;
+        }
+
+        // Convert speed to segments per second using the current segment's length
+        let segment = path.progress.floor() as usize;
+        let length = path.points[segment].distance(path.points[segment + 1]);
+        let step = if length > 0.0 {
+            path.speed * scaled_delta(&time, submerged) / length
+        } else {
+            1.0
+        };
+
+        let mut progress = path.progress + step;
+        let mut movement = path.sample(progress) - path.sample(path.progress);
+        if path.looping && progress >= segments as f32 {
+            // Repeat the path from where it ended instead of jumping back
+            progress -= segments as f32;
+            movement += path.sample(progress) - path.sample(0.0);
+        }
+
+        transform.translation += movement.extend(0.0);
+        path.progress = progress;
+
+        if !path.looping && progress >= segments as f32 {
+            commands
+                .entity(entity)
+                .remove::<SplinePath>()
+                .insert(AutoMove::new(path.end_direction(), path.speed));
+        }
+    }
+}
+
+/// Applies gravity to bouncing entities and bounces them off platform tops
+/// and ramp surfaces
+#[allow(clippy::type_complexity)]
+pub fn bounce_obstacles(
+    time: Res<Time>,
+    mut query: Query<(
+        &mut Bounce,
+        &mut Transform,
+        &BoxCollider,
+        Option<&CollisionLayers>,
+        Option<&Submerged>,
+    )>,
+    platform_query: Query<
+        (
+            &Transform,
+            &BoxCollider,
+            Option<&Slope>,
+            Option<&CollisionLayers>,
+        ),
+        (With<Platform>, Without<Bounce>),
+    >,
+    spatial_hash: Res<SpatialHash>,
+) {
+    for (mut bounce, mut transform, collider, layers, submerged) in query.iter_mut() {
+        let delta = scaled_delta(&time, submerged);
+        let layers = layers.copied().unwrap_or_default();
+        bounce.velocity_y -= bounce.gravity * delta;
+        let previous_bottom = transform.translation.y - collider.height / 2.0;
+        transform.translation.y += bounce.velocity_y * delta;
+        if bounce.velocity_y > 0.0 {
+            continue;
+        }
+
+        let bottom = transform.translation.y - collider.height / 2.0;
+        let half_width = collider.width / 2.0;
+        // Platforms are static, so last tick's grid is up to date for them
+        let swept = Rect::new(
+            transform.translation.x - half_width,
+            bottom,
+            transform.translation.x + half_width,
+            previous_bottom,
+        );
+        let nearby = spatial_hash.query(swept);
+        for (platform_transform, platform_collider, slope, platform_layers) in
+            platform_query.iter_many(&nearby)
+        {
+            if !layers.interacts_with(&platform_layers.copied().unwrap_or_default()) {
+                continue;
+            }
+            let platform_bounds = platform_collider.bounds(platform_transform.translation);
+            // Ramps are only as high as their surface under the obstacle
+            let platform_top = slope.map_or(platform_bounds.max.y, |slope| {
+                slope.surface_at(transform.translation.x, platform_bounds)
+            });
+            let horizontal_overlap = (transform.translation.x - platform_transform.translation.x)
+                .abs()
+                < half_width + platform_collider.width / 2.0;
+
+            // Bounce when crossing the platform top on the way down
+            if horizontal_overlap && previous_bottom >= platform_top && bottom <= platform_top {
+                transform.translation.y = platform_top + collider.height / 2.0;
+                bounce.velocity_y = -bounce.velocity_y * bounce.restitution;
+                break;
+            }
+        }
+    }
+}
+
+/// Moves patrolling entities back and forth around their origin
+pub fn patrol_enemies(
+    time: Res<Time>,
+    mut query: Query<(&mut Patrol, &mut Transform, Option<&Submerged>)>,
+) {
+    for (mut patrol, mut transform, submerged) in query.iter_mut() {
+        if patrol.distance <= 0.0 {
+            continue;
+        }
+
+        transform.translation.x += patrol.direction * patrol.speed * scaled_delta(&time, submerged);
+
+        let offset = transform.translation.x - patrol.origin_x;
+        if offset.abs() >= patrol.distance {
+            // Clamp to the end of the route and turn around
+            transform.translation.x = patrol.origin_x + patrol.distance * offset.signum();
+            patrol.direction = -offset.signum();
+        }
+    }
+}
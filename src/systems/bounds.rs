@@ -0,0 +1,175 @@
+//! Kill zones and level bounds
+
+use bevy::prelude::*;
+
+use crate::components::{
+    AutoMove, Health, KillZone, LevelEntity, MovementMode, Obstacle, Patrol, Player, SplinePath,
+    Velocity,
+};
+use crate::events::TriggerEntered;
+use crate::game::config::GameConfig;
+use crate::level::LevelData;
+use crate::resources::{ActiveLevel, CameraView, EntityPool};
+
+/// Hurts or kills the player when they enter a [`KillZone`], then respawns
+/// them
+#[allow(clippy::type_complexity)]
+pub fn check_kill_zones(
+    mut entered: EventReader<TriggerEntered>,
+    kill_zones: Query<&KillZone>,
+    mut players: Query<
+        (
+            &mut Transform,
+            &mut Velocity,
+            &mut Health,
+            Option<&mut MovementMode>,
+        ),
+        With<Player>,
+    >,
+    active_level: Res<ActiveLevel>,
+    levels: Res<Assets<LevelData>>,
+) {
+    let spawn = levels
+        .get(&active_level.handle)
+        .map(|level| level.player_spawn);
+    // Entering two zones at once only counts once
+    let mut respawned = Vec::new();
+
+    for event in entered.read() {
+        if respawned.contains(&event.entity) {
+            continue;
+        }
+        let (Ok(kill_zone), Ok(player)) =
+            (kill_zones.get(event.trigger), players.get_mut(event.entity))
+        else {
+            continue;
+        };
+        hurt_and_respawn(player, kill_zone.damage, spawn);
+        respawned.push(event.entity);
+    }
+}
+
+/// Hurts or kills the player when they leave the active level's bounds,
+/// then respawns them
+#[allow(clippy::type_complexity)]
+pub fn check_level_bounds(
+    config: Res<GameConfig>,
+    active_level: Res<ActiveLevel>,
+    levels: Res<Assets<LevelData>>,
+    mut players: Query<
+        (
+            &mut Transform,
+            &mut Velocity,
+            &mut Health,
+            Option<&mut MovementMode>,
+        ),
+        With<Player>,
+    >,
+) {
+    let Some(level) = levels.get(&active_level.handle) else {
+        return;
+    };
+    let Some(bounds) = level.bounds else {
+        return;
+    };
+
+    for player in players.iter_mut() {
+        if !bounds.contains(player.0.translation.truncate()) {
+            hurt_and_respawn(
+                player,
+                config.player.out_of_bounds_damage,
+                Some(level.player_spawn),
+            );
+        }
+    }
+}
+
+/// Deals `damage` to the player, or kills them when it is `None`, and puts
+/// them back at `spawn`. A player who died comes back with full health.
+pub(super) fn hurt_and_respawn(
+    (mut transform, mut velocity, mut health, mode): (
+        Mut<Transform>,
+        Mut<Velocity>,
+        Mut<Health>,
+        Option<Mut<MovementMode>>,
+    ),
+    damage: Option<f32>,
+    spawn: Option<Vec2>,
+) {
+    let damage = damage.unwrap_or(health.current);
+    health.take_damage(damage);
+    if health.is_dead() {
+        let max = health.max;
+        health.heal(max);
+    }
+
+    if let Some(spawn) = spawn {
+        transform.translation = spawn.extend(transform.translation.z);
+    }
+    velocity.0 = Vec2::ZERO;
+    if let Some(mut mode) = mode {
+        *mode = MovementMode::Normal;
+    }
+}
+
+/// Removes everything but the player that leaves the active level's bounds,
+/// and obstacles that are past the despawn margin outside the camera view
+/// and moving away from it. Obstacles go back to their pool.
+///
+/// Only movers are checked, and entities of neighbouring levels are left to
+/// their own level's bounds.
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub fn despawn_out_of_bounds(
+    mut commands: Commands,
+    config: Res<GameConfig>,
+    camera_view: Res<CameraView>,
+    active_level: Res<ActiveLevel>,
+    levels: Res<Assets<LevelData>>,
+    mut pool: ResMut<EntityPool<Obstacle>>,
+    query: Query<
+        (
+            Entity,
+            &Transform,
+            Option<&AutoMove>,
+            Option<&LevelEntity>,
+            Has<Obstacle>,
+        ),
+        (
+            Or<(
+                With<Obstacle>,
+                With<Velocity>,
+                With<AutoMove>,
+                With<Patrol>,
+                With<SplinePath>,
+            )>,
+            Without<Player>,
+        ),
+    >,
+) {
+    let bounds = levels
+        .get(&active_level.handle)
+        .and_then(|level| level.bounds);
+
+    for (entity, transform, auto_move, level_entity, is_obstacle) in query.iter() {
+        if level_entity.is_some_and(|level_entity| level_entity.0 != active_level.handle.id()) {
+            continue;
+        }
+        let position = transform.translation.truncate();
+        let out_of_bounds = bounds.is_some_and(|bounds| !bounds.contains(position));
+        let off_screen = is_obstacle
+            && camera_view.has_left(
+                position,
+                auto_move.map(|auto_move| auto_move.direction),
+                config.obstacles.despawn_margin,
+            );
+
+        if !(out_of_bounds || off_screen) {
+            continue;
+        }
+        if is_obstacle {
+            pool.release(&mut commands, entity);
+        } else {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
@@ -3,26 +3,44 @@
 use bevy::prelude::*;
 
 use crate::components::{CameraFollow, MainCamera, Player};
-use crate::game::constants::camera::TARGET_FRAMERATE;
+use crate::game::config::GameConfig;
+use crate::resources::{CameraBounds, CameraView};
 
 /// Updates camera to follow the target entity smoothly
 pub fn camera_follow_system(
     time: Res<Time>,
+    config: Res<GameConfig>,
+    camera_bounds: Res<CameraBounds>,
     player_query: Query<&Transform, (With<Player>, Without<MainCamera>)>,
-    mut camera_query: Query<(&mut Transform, &CameraFollow), With<MainCamera>>,
+    mut camera_query: Query<
+        (
+            &mut Transform,
+            &CameraFollow,
+            Option<&OrthographicProjection>,
+        ),
+        With<MainCamera>,
+    >,
 ) {
     let Ok(player_transform) = player_query.get_single() else {
         return;
     };
 
-    for (mut camera_transform, camera_follow) in camera_query.iter_mut() {
-        let target_position = player_transform.translation + camera_follow.offset;
+    for (mut camera_transform, camera_follow, projection) in camera_query.iter_mut() {
+        let mut target_position = player_transform.translation + camera_follow.offset;
+
+        if let Some(bounds) = camera_bounds.0 {
+            let half_view = projection.map_or(Vec2::ZERO, |projection| projection.area.half_size());
+            target_position.x =
+                clamp_to_bounds(target_position.x, bounds.min.x, bounds.max.x, half_view.x);
+            target_position.y =
+                clamp_to_bounds(target_position.y, bounds.min.y, bounds.max.y, half_view.y);
+        }
 
         // Smooth camera movement using lerp (frame-rate independent)
         let lerp_factor = 1.0
             - camera_follow
                 .smoothing
-                .powf(time.delta_seconds() * TARGET_FRAMERATE);
+                .powf(time.delta_seconds() * config.camera.target_framerate);
         camera_transform.translation.x = camera_transform
             .translation
             .x
@@ -34,9 +52,29 @@ pub fn camera_follow_system(
     }
 }
 
+/// Records the world-space area the main camera sees
+pub fn update_camera_view(
+    mut camera_view: ResMut<CameraView>,
+    camera_query: Query<(&Transform, &OrthographicProjection), With<MainCamera>>,
+) {
+    let Ok((transform, projection)) = camera_query.get_single() else {
+        return;
+    };
+
+    let area = projection.area;
+    let view = CameraView(Rect::from_center_size(
+        transform.translation.truncate() + area.center(),
+        area.size(),
+    ));
+    if *camera_view != view {
+        *camera_view = view;
+    }
+}
+
 /// Sets up the camera to follow a specific target entity
 pub fn setup_camera_follow(
     mut commands: Commands,
+    config: Res<GameConfig>,
     camera_query: Query<Entity, With<MainCamera>>,
     player_query: Query<Entity, With<Player>>,
 ) {
@@ -45,8 +83,20 @@ pub fn setup_camera_follow(
     {
         commands.entity(camera_entity).insert(
             CameraFollow::new(player_entity)
-                .with_offset(Vec3::new(0.0, 50.0, 0.0))
-                .with_smoothing(0.05),
+                .with_offset(config.camera.offset.extend(0.0))
+                .with_smoothing(config.camera.smoothing),
         );
     }
 }
+
+/// Clamps a camera center so the view stays inside `min..max`,
+/// centering it when the bounds are smaller than the view
+fn clamp_to_bounds(value: f32, min: f32, max: f32, half_view: f32) -> f32 {
+    let low = min + half_view;
+    let high = max - half_view;
+    if low > high {
+        (min + max) / 2.0
+    } else {
+        value.clamp(low, high)
+    }
+}
@@ -2,57 +2,512 @@
 
 use bevy::prelude::*;
 
-use crate::components::{BoxCollider, DamageOnContact, Health, Obstacle, Player};
-use crate::game::constants::scoring::OBSTACLE_SURVIVE_POINTS;
-use crate::resources::Score;
+use super::bounds::hurt_and_respawn;
+use crate::components::{
+    AutoMove, BoxCollider, Collectible, Collider, CollisionLayers, DamageOnContact, Enemy, Health,
+    MovementMode, Obstacle, OneWayPlatform, Patrol, Platform, Player, PressurePlate, Slope,
+    SplinePath, Trigger, Velocity,
+};
+use crate::events::{Collision, Crushed, TriggerEntered, TriggerExited};
+use crate::game::config::GameConfig;
+use crate::level::LevelData;
+use crate::resources::{ActiveLevel, EntityPool, Score, SpatialHash, TileGrid};
 
-/// Checks for collisions between player and obstacles
+/// Upward velocity given to the player after stomping an enemy
+const STOMP_BOUNCE_VELOCITY: f32 = 300.0;
+/// Upward velocity given to the player when knocked back by an enemy
+const KNOCKBACK_VELOCITY: f32 = 200.0;
+/// Distance at which the player counts as touching a hazard tile, so
+/// standing on top of spikes hurts
+const HAZARD_CONTACT_MARGIN: f32 = 1.0;
+/// Distance at which a solid counts as pressing on a body, so one resting on
+/// the ground is pressed by it
+const CRUSH_CONTACT_MARGIN: f32 = 1.0;
+/// How much less room than its size a body can be squeezed into before it
+/// is crushed
+const CRUSH_TOLERANCE: f32 = 8.0;
+
+/// Keeps the [`SpatialHash`] in sync with moved, resized and removed colliders
 #[allow(clippy::type_complexity)]
-pub fn check_obstacle_collisions(
+pub fn update_spatial_hash(
+    mut spatial_hash: ResMut<SpatialHash>,
+    mut removed: RemovedComponents<BoxCollider>,
+    changed: Query<
+        (Entity, &Transform, &BoxCollider),
+        Or<(Changed<Transform>, Changed<BoxCollider>)>,
+    >,
+) {
+    // Removals first, so entities recycled this frame are added back below
+    for entity in removed.read() {
+        spatial_hash.remove(entity);
+    }
+    for (entity, transform, collider) in changed.iter() {
+        spatial_hash.insert(entity, collider.bounds(transform.translation));
+    }
+}
+
+/// Sends a [`Collision`] for every overlapping pair of colliders whose
+/// [`CollisionLayers`] interact, testing their [`Collider`] shapes when they
+/// have one.
+///
+/// Platforms never move on their own, so only other colliders look for
+/// contacts; they still collide with platforms whose layers allow it.
+/// [`Trigger`]s are left to [`update_triggers`].
+#[allow(clippy::type_complexity)]
+pub fn detect_collisions(
+    movers: Query<
+        (
+            Entity,
+            &Transform,
+            &BoxCollider,
+            Option<&Collider>,
+            Option<&CollisionLayers>,
+        ),
+        (Without<Platform>, Without<Trigger>),
+    >,
+    colliders: Query<
+        (
+            &Transform,
+            &BoxCollider,
+            Option<&Collider>,
+            Option<&CollisionLayers>,
+        ),
+        Without<Trigger>,
+    >,
+    spatial_hash: Res<SpatialHash>,
+    mut collisions: EventWriter<Collision>,
+) {
+    for (entity, transform, collider, shape, layers) in movers.iter() {
+        let layers = layers.copied().unwrap_or_default();
+        let shape = shape.copied().unwrap_or_else(|| Collider::from(collider));
+        let position = transform.translation.truncate();
+
+        for other in spatial_hash.query(collider.bounds(transform.translation)) {
+            // Pairs of movers are found from both sides, keep one
+            if other == entity || (other < entity && movers.contains(other)) {
+                continue;
+            }
+            let Ok((other_transform, other_collider, other_shape, other_layers)) =
+                colliders.get(other)
+            else {
+                continue;
+            };
+            if !layers.interacts_with(&other_layers.copied().unwrap_or_default()) {
+                continue;
+            }
+
+            let other_shape = other_shape
+                .copied()
+                .unwrap_or_else(|| Collider::from(other_collider));
+            let other_position = other_transform.translation.truncate();
+            if let Some(contact) = shape.contact(position, &other_shape, other_position) {
+                collisions.send(Collision {
+                    a: entity,
+                    b: other,
+                    normal: contact.normal,
+                    depth: contact.depth,
+                });
+            }
+        }
+    }
+}
+
+/// Tracks which colliders overlap each [`Trigger`], sending
+/// [`TriggerEntered`] and [`TriggerExited`] as they come and go
+#[allow(clippy::type_complexity)]
+pub fn update_triggers(
+    mut triggers: Query<(
+        Entity,
+        &Transform,
+        &BoxCollider,
+        Option<&Collider>,
+        Option<&CollisionLayers>,
+        &mut Trigger,
+    )>,
+    bodies: Query<
+        (
+            &Transform,
+            &BoxCollider,
+            Option<&Collider>,
+            Option<&CollisionLayers>,
+        ),
+        (Without<Trigger>, Or<(Without<Platform>, With<Velocity>)>),
+    >,
+    spatial_hash: Res<SpatialHash>,
+    mut entered: EventWriter<TriggerEntered>,
+    mut exited: EventWriter<TriggerExited>,
+) {
+    for (trigger_entity, transform, collider, shape, layers, mut trigger) in triggers.iter_mut() {
+        let filter = layers.map_or(u32::MAX, |layers| layers.filter);
+        let shape = shape.copied().unwrap_or_else(|| Collider::from(collider));
+        let position = transform.translation.truncate();
+
+        let current = spatial_hash
+            .query(collider.bounds(transform.translation))
+            .into_iter()
+            .filter(|&entity| {
+                let Ok((other_transform, other_collider, other_shape, other_layers)) =
+                    bodies.get(entity)
+                else {
+                    return false;
+                };
+                let membership = other_layers.map_or(u32::MAX, |layers| layers.membership);
+                let other_shape = other_shape
+                    .copied()
+                    .unwrap_or_else(|| Collider::from(other_collider));
+                filter & membership != 0
+                    && shape
+                        .contact(
+                            position,
+                            &other_shape,
+                            other_transform.translation.truncate(),
+                        )
+                        .is_some()
+            })
+            .collect();
+
+        let (entities_entered, entities_exited) = trigger.update(current);
+        entered.send_batch(entities_entered.into_iter().map(|entity| TriggerEntered {
+            trigger: trigger_entity,
+            entity,
+        }));
+        exited.send_batch(entities_exited.into_iter().map(|entity| TriggerExited {
+            trigger: trigger_entity,
+            entity,
+        }));
+    }
+}
+
+/// Holds down each [`PressurePlate`] while its [`Trigger`] has anything on
+/// it, drawing pressed plates at half height
+pub fn update_pressure_plates(mut plates: Query<(&Trigger, &mut PressurePlate, &mut Transform)>) {
+    for (trigger, mut plate, mut transform) in plates.iter_mut() {
+        let pressed = !trigger.occupants().is_empty();
+        if plate.pressed != pressed {
+            plate.pressed = pressed;
+            transform.scale.y = if pressed { 0.5 } else { 1.0 };
+        }
+    }
+}
+
+/// Sends [`Crushed`] for movers squeezed between solids on opposite sides
+/// that leave less room than the mover's size, so it cannot be pushed out.
+///
+/// Solids are platforms, except one-way ones, whose layers interact with
+/// the mover's. At least one of the solids must be moving: walking into
+/// static geometry or a resting crate is never a crush.
+#[allow(clippy::type_complexity)]
+pub fn detect_crushing(
+    bodies: Query<
+        (Entity, &Transform, &BoxCollider, Option<&CollisionLayers>),
+        (
+            Or<(
+                With<Velocity>,
+                With<AutoMove>,
+                With<Patrol>,
+                With<SplinePath>,
+            )>,
+            Without<Platform>,
+        ),
+    >,
+    solids: Query<
+        (
+            &Transform,
+            &BoxCollider,
+            Option<&Slope>,
+            Option<&CollisionLayers>,
+        ),
+        (With<Platform>, Without<OneWayPlatform>),
+    >,
+    moving: Query<
+        Option<&Velocity>,
+        Or<(
+            With<Velocity>,
+            With<AutoMove>,
+            With<Patrol>,
+            With<SplinePath>,
+        )>,
+    >,
+    spatial_hash: Res<SpatialHash>,
+    mut crushed: EventWriter<Crushed>,
+) {
+    for (entity, transform, collider, layers) in bodies.iter() {
+        let layers = layers.copied().unwrap_or_default();
+        let area = collider
+            .bounds(transform.translation)
+            .inflate(CRUSH_CONTACT_MARGIN);
+        let center = area.center();
+        let mut vertical = Squeeze::default();
+        let mut horizontal = Squeeze::default();
+
+        for solid in spatial_hash.query(area) {
+            let Ok((solid_transform, solid_collider, slope, solid_layers)) = solids.get(solid)
+            else {
+                continue;
+            };
+            if !layers.interacts_with(&solid_layers.copied().unwrap_or_default()) {
+                continue;
+            }
+            let mut bounds = solid_collider.bounds(solid_transform.translation);
+            // Ramps only reach up to their surface under the body
+            if let Some(slope) = slope {
+                bounds.max.y = slope.surface_at(center.x, bounds);
+            }
+            let overlap = area.intersect(bounds);
+            if overlap.is_empty() {
+                continue;
+            }
+
+            // A solid presses along the axis it overlaps the body least on
+            let moving = moving
+                .get(solid)
+                .is_ok_and(|velocity| velocity.is_none_or(|velocity| velocity.0 != Vec2::ZERO));
+            if overlap.width() < overlap.height() {
+                if bounds.center().x < center.x {
+                    horizontal.press_low(bounds.max.x, solid, moving);
+                } else {
+                    horizontal.press_high(bounds.min.x, solid, moving);
+                }
+            } else if bounds.center().y < center.y {
+                vertical.press_low(bounds.max.y, solid, moving);
+            } else {
+                vertical.press_high(bounds.min.y, solid, moving);
+            }
+        }
+
+        let solids = vertical
+            .crushing(collider.height)
+            .or_else(|| horizontal.crushing(collider.width));
+        if let Some(solids) = solids {
+            crushed.send(Crushed { entity, solids });
+        }
+    }
+}
+
+/// The solids pressing on a body from either side along one axis
+#[derive(Debug, Default)]
+struct Squeeze {
+    /// Nearest face and entity of the solids on the low side
+    low: Option<(f32, Entity)>,
+    /// Nearest face and entity of the solids on the high side
+    high: Option<(f32, Entity)>,
+    moving: bool,
+}
+
+impl Squeeze {
+    fn press_low(&mut self, face: f32, solid: Entity, moving: bool) {
+        if self.low.is_none_or(|(nearest, _)| face > nearest) {
+            self.low = Some((face, solid));
+        }
+        self.moving |= moving;
+    }
+
+    fn press_high(&mut self, face: f32, solid: Entity, moving: bool) {
+        if self.high.is_none_or(|(nearest, _)| face < nearest) {
+            self.high = Some((face, solid));
+        }
+        self.moving |= moving;
+    }
+
+    /// The nearest solids on both sides, if they crush a body of `size`
+    fn crushing(&self, size: f32) -> Option<[Entity; 2]> {
+        let ((low, low_solid), (high, high_solid)) = (self.low?, self.high?);
+        (self.moving && high - low < size - CRUSH_TOLERANCE).then_some([low_solid, high_solid])
+    }
+}
+
+/// Hurts crushed entities. The player takes `player.crush_damage`, or dies
+/// when it is `None`, and respawns; anything else is destroyed, obstacles
+/// going back to their pool.
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
+pub fn apply_crush_damage(
     mut commands: Commands,
-    mut player_query: Query<(&Transform, &BoxCollider, &mut Health), With<Player>>,
-    obstacle_query: Query<
-        (Entity, &Transform, &BoxCollider, &DamageOnContact),
-        (With<Obstacle>, Without<Player>),
+    mut crushed: EventReader<Crushed>,
+    config: Res<GameConfig>,
+    active_level: Res<ActiveLevel>,
+    levels: Res<Assets<LevelData>>,
+    mut pool: ResMut<EntityPool<Obstacle>>,
+    mut players: Query<
+        (
+            &mut Transform,
+            &mut Velocity,
+            &mut Health,
+            Option<&mut MovementMode>,
+        ),
+        With<Player>,
     >,
+    obstacles: Query<(), With<Obstacle>>,
+) {
+    let spawn = levels
+        .get(&active_level.handle)
+        .map(|level| level.player_spawn);
+
+    for event in crushed.read() {
+        if let Ok(player) = players.get_mut(event.entity) {
+            hurt_and_respawn(player, config.player.crush_damage, spawn);
+        } else if obstacles.contains(event.entity) {
+            pool.release(&mut commands, event.entity);
+        } else {
+            commands.entity(event.entity).despawn_recursive();
+        }
+    }
+}
+
+/// Damages the player when an obstacle hits them
+#[allow(clippy::too_many_arguments)]
+pub fn check_obstacle_collisions(
+    mut commands: Commands,
+    mut collisions: EventReader<Collision>,
+    mut player_query: Query<&mut Health, With<Player>>,
+    obstacle_query: Query<&DamageOnContact, With<Obstacle>>,
+    config: Res<GameConfig>,
     mut score: ResMut<Score>,
+    mut pool: ResMut<EntityPool<Obstacle>>,
 ) {
-    let Ok((player_transform, player_collider, mut player_health)) = player_query.get_single_mut()
-    else {
-        return;
-    };
+    for collision in collisions.read() {
+        let Some((player, obstacle)) = collision.pair_with(|entity| player_query.contains(entity))
+        else {
+            continue;
+        };
+        let (Ok(mut player_health), Ok(damage)) =
+            (player_query.get_mut(player), obstacle_query.get(obstacle))
+        else {
+            continue;
+        };
 
-    let player_half_width = player_collider.width / 2.0;
-    let player_half_height = player_collider.height / 2.0;
+        // Apply damage to player
+        player_health.take_damage(damage.damage);
 
-    for (obstacle_entity, obstacle_transform, obstacle_collider, damage) in obstacle_query.iter() {
-        let obstacle_half_width = obstacle_collider.width / 2.0;
-        let obstacle_half_height = obstacle_collider.height / 2.0;
+        // Remove the obstacle
+        pool.release(&mut commands, obstacle);
 
-        // AABB collision check
-        let player_left = player_transform.translation.x - player_half_width;
-        let player_right = player_transform.translation.x + player_half_width;
-        let player_bottom = player_transform.translation.y - player_half_height;
-        let player_top = player_transform.translation.y + player_half_height;
+        // Add score for surviving collision
+        score.add(config.scoring.obstacle_survive_points);
+    }
+}
 
-        let obstacle_left = obstacle_transform.translation.x - obstacle_half_width;
-        let obstacle_right = obstacle_transform.translation.x + obstacle_half_width;
-        let obstacle_bottom = obstacle_transform.translation.y - obstacle_half_height;
-        let obstacle_top = obstacle_transform.translation.y + obstacle_half_height;
+/// Resolves collisions between the player and enemies.
+///
+/// Landing on an enemy while falling stomps it; any other contact damages
+/// the player and pushes them out of the enemy.
+#[allow(clippy::type_complexity)]
+pub fn check_enemy_collisions(
+    mut commands: Commands,
+    mut collisions: EventReader<Collision>,
+    mut player_query: Query<
+        (&mut Transform, &mut Velocity, &BoxCollider, &mut Health),
+        With<Player>,
+    >,
+    enemy_query: Query<
+        (&Transform, &BoxCollider, &DamageOnContact),
+        (With<Enemy>, Without<Player>),
+    >,
+    config: Res<GameConfig>,
+    mut score: ResMut<Score>,
+) {
+    for collision in collisions.read() {
+        let Some((player, enemy)) = collision.pair_with(|entity| player_query.contains(entity))
+        else {
+            continue;
+        };
+        let (
+            Ok((mut player_transform, mut velocity, player_collider, mut player_health)),
+            Ok((enemy_transform, enemy_collider, damage)),
+        ) = (player_query.get_mut(player), enemy_query.get(enemy))
+        else {
+            continue;
+        };
+
+        // Measured now, as earlier responses may have moved the player
+        let delta = (player_transform.translation - enemy_transform.translation).truncate();
+        let overlap_x = (player_collider.width + enemy_collider.width) / 2.0 - delta.x.abs();
+        let overlap_y = (player_collider.height + enemy_collider.height) / 2.0 - delta.y.abs();
 
-        // Check for overlap
-        let horizontal_overlap = player_left < obstacle_right && player_right > obstacle_left;
-        let vertical_overlap = player_bottom < obstacle_top && player_top > obstacle_bottom;
+        if overlap_x <= 0.0 || overlap_y <= 0.0 {
+            continue;
+        }
 
-        if horizontal_overlap && vertical_overlap {
-            // Apply damage to player
+        // Stomp: falling onto the enemy from above with a mostly vertical overlap
+        if velocity.0.y < 0.0 && delta.y > 0.0 && overlap_y < overlap_x {
+            commands.entity(enemy).despawn();
+            velocity.0.y = STOMP_BOUNCE_VELOCITY;
+            score.add(config.scoring.enemy_stomp_points);
+        } else {
             player_health.take_damage(damage.damage);
+            let push = if delta.x < 0.0 { -1.0 } else { 1.0 };
+            player_transform.translation.x += push * overlap_x;
+            velocity.0.y = KNOCKBACK_VELOCITY;
+        }
+    }
+}
+
+/// Turns patrolling enemies around when they bump into each other, for
+/// configs where enemies collide with enemies
+pub fn turn_colliding_enemies(
+    mut collisions: EventReader<Collision>,
+    mut enemy_query: Query<(&Transform, &mut Patrol), With<Enemy>>,
+) {
+    for collision in collisions.read() {
+        let Ok([(transform_a, mut patrol_a), (transform_b, mut patrol_b)]) =
+            enemy_query.get_many_mut([collision.a, collision.b])
+        else {
+            continue;
+        };
+
+        // Each walks away from the other
+        let away = if transform_a.translation.x < transform_b.translation.x {
+            -1.0
+        } else {
+            1.0
+        };
+        patrol_a.direction = away;
+        patrol_b.direction = -away;
+    }
+}
+
+/// Picks up collectibles touched by the player
+pub fn check_collectible_pickups(
+    mut commands: Commands,
+    mut collisions: EventReader<Collision>,
+    player_query: Query<(), With<Player>>,
+    collectible_query: Query<&Collectible>,
+    mut score: ResMut<Score>,
+) {
+    for collision in collisions.read() {
+        let Some((_, entity)) = collision.pair_with(|entity| player_query.contains(entity)) else {
+            continue;
+        };
+        if let Ok(collectible) = collectible_query.get(entity) {
+            commands.entity(entity).despawn();
+            score.add(collectible.points);
+        }
+    }
+}
+
+/// Damages the player over time while touching hazard tiles
+pub fn check_tile_hazards(
+    time: Res<Time>,
+    tile_grid: Res<TileGrid>,
+    mut player_query: Query<(&Transform, &BoxCollider, &mut Health), With<Player>>,
+) {
+    let Some(tilemap) = &tile_grid.0 else {
+        return;
+    };
 
-            // Remove the obstacle
-            commands.entity(obstacle_entity).despawn();
+    for (transform, collider, mut health) in player_query.iter_mut() {
+        let area = Rect::from_center_size(
+            transform.translation.truncate(),
+            Vec2::new(collider.width, collider.height),
+        )
+        .inflate(HAZARD_CONTACT_MARGIN);
 
-            // Add score for surviving collision
-            score.add(OBSTACLE_SURVIVE_POINTS);
+        // Overlapping several hazard tiles hurts as much as the worst one
+        let damage_per_second = tilemap
+            .tiles_in(area)
+            .filter_map(|tile| tile.hazard)
+            .fold(0.0, f32::max);
+        if damage_per_second > 0.0 {
+            health.take_damage(damage_per_second * time.delta_seconds());
         }
     }
 }
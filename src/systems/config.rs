@@ -0,0 +1,112 @@
+//! Config systems: loading the config file and applying it to live entities
+
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+
+use crate::components::{
+    Breath, CameraFollow, CollisionLayers, DamageOnContact, Gravity, Health, JumpConfig, MaxSlope,
+    Obstacle, Player, Speed,
+};
+use crate::game::config::GameConfig;
+use crate::resources::{ConfigFile, Difficulty};
+
+/// Starts loading the config file so changes to it can be picked up
+pub fn load_config(asset_server: Res<AssetServer>, mut config_file: ResMut<ConfigFile>) {
+    config_file.handle = asset_server.load(config_file.path.clone());
+}
+
+/// Replaces the [`GameConfig`] resource when the config file loads or changes,
+/// keeping the command line overrides. A file that is invalid once the
+/// overrides are applied leaves the current config in place.
+pub fn apply_config(
+    mut events: EventReader<AssetEvent<GameConfig>>,
+    config_file: Res<ConfigFile>,
+    assets: Res<Assets<GameConfig>>,
+    mut config: ResMut<GameConfig>,
+) {
+    for event in events.read() {
+        if !(event.is_loaded_with_dependencies(&config_file.handle)
+            || event.is_modified(&config_file.handle))
+        {
+            continue;
+        }
+        let Some(loaded) = assets.get(&config_file.handle) else {
+            continue;
+        };
+
+        match loaded.clone().with_overrides(&config_file.overrides) {
+            Ok(loaded) => {
+                *config = loaded;
+                info!("Applied config from {}", config_file.path);
+            }
+            Err(error) => error!("Ignoring {}: {error}", config_file.path),
+        }
+    }
+}
+
+/// Re-applies changed config to entities and the window
+#[allow(clippy::type_complexity)]
+pub fn apply_config_to_entities(
+    config: Res<GameConfig>,
+    difficulty: Res<Difficulty>,
+    mut player_query: Query<
+        (
+            &mut Speed,
+            &mut Health,
+            &mut Gravity,
+            &mut JumpConfig,
+            &mut MaxSlope,
+            Option<&mut Breath>,
+        ),
+        With<Player>,
+    >,
+    mut obstacle_query: Query<&mut DamageOnContact, With<Obstacle>>,
+    mut layers_query: Query<&mut CollisionLayers>,
+    mut camera_query: Query<&mut CameraFollow>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    let player = &config.player;
+    for (mut speed, mut health, mut gravity, mut jump_config, mut max_slope, breath) in
+        player_query.iter_mut()
+    {
+        speed.0 = player.speed;
+        health.max = player.max_health;
+        health.current = health.current.min(health.max);
+        gravity.0 = player.gravity;
+        jump_config.jump_velocity = player.jump_velocity;
+        jump_config.jump_cut_multiplier = player.jump_cut_multiplier;
+        jump_config.stroke_velocity = player.swim_stroke_velocity;
+        max_slope.0 = player.max_slope_angle.to_radians();
+        if let (Some(mut breath), Some(max)) = (breath, player.breath) {
+            breath.max = max;
+            breath.remaining = breath.remaining.min(max);
+            breath.drowning_damage = player.drowning_damage;
+        }
+    }
+
+    // The spawn interval is retimed by `update_difficulty`
+    for mut damage in obstacle_query.iter_mut() {
+        damage.damage = config.obstacles.damage * difficulty.damage;
+    }
+
+    for mut layers in layers_query.iter_mut() {
+        *layers = config.collision.layers_for(layers.membership);
+    }
+
+    for mut camera_follow in camera_query.iter_mut() {
+        camera_follow.offset = config.camera.offset.extend(0.0);
+        camera_follow.smoothing = config.camera.smoothing;
+    }
+
+    for mut window in window_query.iter_mut() {
+        let settings = &config.window;
+        if window.title != settings.title {
+            window.title.clone_from(&settings.title);
+        }
+        if window.resolution.width() != settings.width
+            || window.resolution.height() != settings.height
+        {
+            window.resolution.set(settings.width, settings.height);
+        }
+    }
+}
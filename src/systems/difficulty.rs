@@ -0,0 +1,27 @@
+//! Difficulty director: ramps obstacle spawning with survival time
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::game::config::GameConfig;
+use crate::resources::{Difficulty, GameTimer, ObstacleSpawnTimer};
+
+/// Samples the difficulty curves at the current game time and retimes the
+/// obstacle spawner to match
+pub fn update_difficulty(
+    game_timer: Res<GameTimer>,
+    config: Res<GameConfig>,
+    mut difficulty: ResMut<Difficulty>,
+    mut spawn_timer: ResMut<ObstacleSpawnTimer>,
+) {
+    let sampled = config.difficulty.sample(game_timer.elapsed);
+    if *difficulty != sampled {
+        *difficulty = sampled;
+    }
+
+    let interval = Duration::from_secs_f32(config.obstacles.scaled(&difficulty).spawn_interval);
+    if spawn_timer.timer.duration() != interval {
+        spawn_timer.timer.set_duration(interval);
+    }
+}
@@ -0,0 +1,813 @@
+//! Level flow systems: level loading and streaming, goal detection,
+//! results screen and level advancing
+
+use bevy::asset::LoadState;
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::sprite::{MaterialMesh2dBundle, Mesh2dHandle};
+use bevy::utils::HashSet;
+
+use crate::components::{
+    BoxCollider, Climbable, Collectible, CollisionLayer, CollisionLayers, DamageOnContact,
+    DynamicBody, Enemy, Gravity, Grounded, Health, KillZone, LevelEntity, LevelGoal, Obstacle,
+    OneWayPlatform, Patrol, Platform, Player, PressurePlate, Pushable, ResultsScreen, Slope,
+    Tilemap, Trigger, Velocity,
+};
+use crate::events::Collision;
+use crate::game::config::{CollisionConfig, GameConfig};
+use crate::level::{LevelData, RampData, TileMapData};
+use crate::resources::{
+    ActiveLevel, CameraBounds, EntityPool, GameTimer, LevelList, LevelResults, Score, TileGrid,
+    WaveState,
+};
+use crate::states::GameState;
+
+/// Starts loading every level in the level list
+pub fn load_levels(asset_server: Res<AssetServer>, mut level_list: ResMut<LevelList>) {
+    level_list.handles = level_list
+        .levels
+        .iter()
+        .map(|path| asset_server.load(path.clone()))
+        .collect();
+}
+
+/// Leaves the loading state once every level has finished loading. Levels
+/// that fail to load are dropped from the list, so play starts at, and
+/// advances through, the ones that did load. When none did, the game stays
+/// on the loading screen.
+pub fn check_levels_loaded(
+    asset_server: Res<AssetServer>,
+    mut level_list: ResMut<LevelList>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut any_failed: Local<bool>,
+) {
+    let mut all_done = true;
+    let mut failed = Vec::new();
+
+    for (index, (path, handle)) in level_list
+        .levels
+        .iter()
+        .zip(&level_list.handles)
+        .enumerate()
+    {
+        match asset_server.load_state(handle) {
+            LoadState::Loaded => {}
+            LoadState::Failed(error) => {
+                // The loader error names the offending entry
+                error!("Failed to load level {path}: {error}");
+                failed.push(index);
+            }
+            _ => all_done = false,
+        }
+    }
+
+    for &index in failed.iter().rev() {
+        level_list.remove(index);
+    }
+    *any_failed |= !failed.is_empty();
+
+    if !all_done {
+        return;
+    }
+    if level_list.levels.is_empty() && *any_failed {
+        if !failed.is_empty() {
+            error!("No level could be loaded");
+        }
+        return;
+    }
+    next_state.set(GameState::Playing);
+}
+
+/// Unloads the previous level and makes the current level of the list
+/// active. Obstacles go back to the pool instead of being despawned.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn load_current_level(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    level_list: Res<LevelList>,
+    levels: Res<Assets<LevelData>>,
+    level_entities: Query<Entity, (With<LevelEntity>, Without<Obstacle>)>,
+    obstacles: Query<Entity, With<Obstacle>>,
+    mut pool: ResMut<EntityPool<Obstacle>>,
+    mut player_query: Query<(&mut Transform, &mut Velocity, &mut Grounded), With<Player>>,
+    mut timer: ResMut<GameTimer>,
+    mut wave_state: ResMut<WaveState>,
+    mut active_level: ResMut<ActiveLevel>,
+    mut clear_color: ResMut<ClearColor>,
+    mut camera_bounds: ResMut<CameraBounds>,
+    mut tile_grid: ResMut<TileGrid>,
+) {
+    for entity in level_entities.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    for entity in obstacles.iter() {
+        pool.release(&mut commands, entity);
+    }
+
+    timer.reset();
+    timer.resume();
+    *wave_state = WaveState::default();
+
+    let Some((handle, level)) = level_list
+        .current_handle()
+        .and_then(|handle| Some((handle, levels.get(handle)?)))
+    else {
+        warn!(
+            "Level {} is not loaded, nothing to spawn",
+            level_list.current_path().unwrap_or("<none>")
+        );
+        *active_level = ActiveLevel::default();
+        *tile_grid = TileGrid::default();
+        return;
+    };
+
+    for (mut transform, mut velocity, mut grounded) in player_query.iter_mut() {
+        transform.translation = level.player_spawn.extend(transform.translation.z);
+        velocity.0 = Vec2::ZERO;
+        grounded.0 = false;
+    }
+
+    activate_level(
+        handle.clone(),
+        level,
+        &asset_server,
+        &mut active_level,
+        &mut clear_color,
+        &mut camera_bounds,
+        &mut tile_grid,
+    );
+}
+
+/// Makes a level active and starts loading its neighbours
+fn activate_level(
+    handle: Handle<LevelData>,
+    level: &LevelData,
+    asset_server: &AssetServer,
+    active_level: &mut ActiveLevel,
+    clear_color: &mut ClearColor,
+    camera_bounds: &mut CameraBounds,
+    tile_grid: &mut TileGrid,
+) {
+    active_level.handle = handle;
+    active_level.neighbours = level
+        .neighbours
+        .iter()
+        .map(|path| asset_server.load(path.clone()))
+        .collect();
+
+    let [r, g, b] = level.background_color;
+    clear_color.0 = Color::srgb(r, g, b);
+    camera_bounds.0 = level.camera_bounds;
+    tile_grid.0 = level.tilemap.clone();
+}
+
+/// Switches the active level when the player walks out of it into a neighbour
+#[allow(clippy::too_many_arguments)]
+pub fn check_level_transition(
+    asset_server: Res<AssetServer>,
+    levels: Res<Assets<LevelData>>,
+    player_query: Query<&Transform, With<Player>>,
+    mut active_level: ResMut<ActiveLevel>,
+    mut clear_color: ResMut<ClearColor>,
+    mut camera_bounds: ResMut<CameraBounds>,
+    mut tile_grid: ResMut<TileGrid>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+    let Some(bounds) = levels
+        .get(&active_level.handle)
+        .and_then(|level| level.world_bounds)
+    else {
+        return;
+    };
+
+    let position = player_transform.translation.truncate();
+    if bounds.contains(position) {
+        return;
+    }
+
+    let entered = active_level.neighbours.iter().find_map(|handle| {
+        let level = levels.get(handle)?;
+        level
+            .world_bounds
+            .is_some_and(|bounds| bounds.contains(position))
+            .then(|| (handle.clone(), level))
+    });
+
+    if let Some((handle, level)) = entered {
+        activate_level(
+            handle,
+            level,
+            &asset_server,
+            &mut active_level,
+            &mut clear_color,
+            &mut camera_bounds,
+            &mut tile_grid,
+        );
+    }
+}
+
+/// Despawns levels whose files changed on disk so [`stream_levels`] respawns
+/// them, refreshing the active level's settings without moving the player
+#[allow(clippy::too_many_arguments)]
+pub fn reload_levels(
+    mut commands: Commands,
+    mut events: EventReader<AssetEvent<LevelData>>,
+    asset_server: Res<AssetServer>,
+    levels: Res<Assets<LevelData>>,
+    level_entities: Query<(Entity, &LevelEntity)>,
+    mut active_level: ResMut<ActiveLevel>,
+    mut clear_color: ResMut<ClearColor>,
+    mut camera_bounds: ResMut<CameraBounds>,
+    mut tile_grid: ResMut<TileGrid>,
+) {
+    for event in events.read() {
+        let AssetEvent::Modified { id } = *event else {
+            continue;
+        };
+        let Some(level) = levels.get(id) else {
+            continue;
+        };
+
+        for (entity, level_entity) in level_entities.iter() {
+            if level_entity.0 == id {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+
+        if active_level.handle.id() == id {
+            let handle = active_level.handle.clone();
+            activate_level(
+                handle,
+                level,
+                &asset_server,
+                &mut active_level,
+                &mut clear_color,
+                &mut camera_bounds,
+                &mut tile_grid,
+            );
+        }
+        info!("Reloaded level {}", level.name);
+    }
+}
+
+/// Keeps the active level and its loaded neighbours spawned, despawning
+/// levels that are no longer adjacent
+#[allow(clippy::too_many_arguments)]
+pub fn stream_levels(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    active_level: Res<ActiveLevel>,
+    levels: Res<Assets<LevelData>>,
+    config: Res<GameConfig>,
+    level_entities: Query<(Entity, &LevelEntity)>,
+) {
+    let wanted: HashSet<AssetId<LevelData>> = active_level
+        .ids()
+        .filter(|id| levels.contains(*id))
+        .collect();
+    let mut spawned = HashSet::new();
+
+    for (entity, level_entity) in level_entities.iter() {
+        if wanted.contains(&level_entity.0) {
+            spawned.insert(level_entity.0);
+        } else {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+
+    for id in wanted.difference(&spawned) {
+        if let Some(level) = levels.get(*id) {
+            spawn_level(
+                &mut commands,
+                *id,
+                level,
+                &config.collision,
+                &asset_server,
+                &mut atlas_layouts,
+                &mut meshes,
+                &mut materials,
+            );
+        }
+    }
+}
+
+/// Spawns all entities described by the level data, tagged with [`LevelEntity`]
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_level(
+    commands: &mut Commands,
+    id: AssetId<LevelData>,
+    level: &LevelData,
+    collision: &CollisionConfig,
+    asset_server: &AssetServer,
+    atlas_layouts: &mut Assets<TextureAtlasLayout>,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<ColorMaterial>,
+) {
+    for platform in &level.platforms {
+        let [r, g, b] = platform.color.unwrap_or([0.3, 0.5, 0.3]); // Green platforms
+        let mut entity = commands.spawn((
+            LevelEntity(id),
+            Platform,
+            BoxCollider::new(platform.size.x, platform.size.y),
+            collision.layers(CollisionLayer::Platform),
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::srgb(r, g, b),
+                    custom_size: Some(platform.size),
+                    ..default()
+                },
+                transform: Transform::from_translation(platform.position.extend(0.0)),
+                ..default()
+            },
+        ));
+        if let Some(material) = platform.material {
+            entity.insert(material);
+        }
+    }
+
+    for platform in &level.one_way_platforms {
+        let [r, g, b] = platform.color.unwrap_or([0.5, 0.4, 0.25]); // Wooden planks
+        let mut entity = commands.spawn((
+            LevelEntity(id),
+            Platform,
+            OneWayPlatform,
+            BoxCollider::new(platform.size.x, platform.size.y),
+            collision.layers(CollisionLayer::Platform),
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::srgb(r, g, b),
+                    custom_size: Some(platform.size),
+                    ..default()
+                },
+                transform: Transform::from_translation(platform.position.extend(0.0)),
+                ..default()
+            },
+        ));
+        if let Some(material) = platform.material {
+            entity.insert(material);
+        }
+    }
+
+    for ramp in &level.ramps {
+        let [r, g, b] = ramp.color.unwrap_or([0.3, 0.5, 0.3]);
+        let mut entity = commands.spawn((
+            LevelEntity(id),
+            Platform,
+            Slope::new(ramp.left_height, ramp.right_height),
+            BoxCollider::new(ramp.size.x, ramp.size.y),
+            collision.layers(CollisionLayer::Platform),
+            MaterialMesh2dBundle {
+                mesh: Mesh2dHandle(meshes.add(ramp_mesh(ramp))),
+                material: materials.add(Color::srgb(r, g, b)),
+                transform: Transform::from_translation(ramp.position.extend(0.0)),
+                ..default()
+            },
+        ));
+        if let Some(material) = ramp.material {
+            entity.insert(material);
+        }
+    }
+
+    for ladder in &level.ladders {
+        let [r, g, b] = ladder.color.unwrap_or([0.55, 0.4, 0.2]);
+        commands.spawn((
+            trigger_volume(id, ladder.size),
+            Climbable,
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::srgb(r, g, b),
+                    custom_size: Some(ladder.size),
+                    ..default()
+                },
+                // Behind the player and platforms
+                transform: Transform::from_translation(ladder.position.extend(-0.1)),
+                ..default()
+            },
+        ));
+    }
+
+    for water in &level.water {
+        let [r, g, b] = water.color.unwrap_or([0.2, 0.45, 0.85]);
+        commands.spawn((
+            trigger_volume(id, water.size),
+            water.physics,
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::srgba(r, g, b, 0.45),
+                    custom_size: Some(water.size),
+                    ..default()
+                },
+                // In front of whatever is submerged
+                transform: Transform::from_translation(water.position.extend(0.5)),
+                ..default()
+            },
+        ));
+    }
+
+    for zone in &level.kill_zones {
+        let mut entity = commands.spawn((
+            LevelEntity(id),
+            KillZone {
+                damage: zone.damage,
+            },
+            Trigger::default(),
+            BoxCollider::new(zone.size.x, zone.size.y),
+            CollisionLayers::new(&[], &[CollisionLayer::Player]),
+            TransformBundle::from_transform(Transform::from_translation(zone.position.extend(0.5))),
+        ));
+        if let Some([r, g, b]) = zone.color {
+            entity.insert((
+                Sprite {
+                    color: Color::srgb(r, g, b),
+                    custom_size: Some(zone.size),
+                    ..default()
+                },
+                Handle::<Image>::default(),
+                VisibilityBundle::default(),
+            ));
+        }
+    }
+
+    for crate_data in &level.crates {
+        let [r, g, b] = crate_data.color.unwrap_or([0.6, 0.42, 0.22]);
+        commands.spawn((
+            LevelEntity(id),
+            Platform,
+            Pushable {
+                mass: crate_data.mass,
+            },
+            DynamicBody,
+            Velocity::default(),
+            Gravity::default(),
+            Grounded::default(),
+            BoxCollider::new(crate_data.size.x, crate_data.size.y),
+            collision.layers(CollisionLayer::Platform),
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::srgb(r, g, b),
+                    custom_size: Some(crate_data.size),
+                    ..default()
+                },
+                transform: Transform::from_translation(crate_data.position.extend(0.0)),
+                ..default()
+            },
+        ));
+    }
+
+    for plate in &level.pressure_plates {
+        let [r, g, b] = plate.color.unwrap_or([0.75, 0.2, 0.2]);
+        commands.spawn((
+            LevelEntity(id),
+            PressurePlate::default(),
+            Trigger::default(),
+            BoxCollider::new(plate.size.x, plate.size.y),
+            // Held down by the player and crates
+            CollisionLayers::new(&[], &[CollisionLayer::Player, CollisionLayer::Platform]),
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::srgb(r, g, b),
+                    custom_size: Some(plate.size),
+                    ..default()
+                },
+                transform: Transform::from_translation(plate.position.extend(0.0)),
+                ..default()
+            },
+        ));
+    }
+
+    for enemy in &level.enemies {
+        commands.spawn((
+            LevelEntity(id),
+            Enemy,
+            Patrol::new(enemy.position.x, enemy.patrol_distance, enemy.speed),
+            DamageOnContact::new(enemy.damage),
+            BoxCollider::new(enemy.size.x, enemy.size.y),
+            collision.layers(CollisionLayer::Enemy),
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::srgb(0.6, 0.2, 0.6), // Purple enemies
+                    custom_size: Some(enemy.size),
+                    ..default()
+                },
+                transform: Transform::from_translation(enemy.position.extend(0.0)),
+                ..default()
+            },
+        ));
+    }
+
+    for collectible in &level.collectibles {
+        let size = Vec2::splat(20.0);
+        commands.spawn((
+            LevelEntity(id),
+            Collectible {
+                points: collectible.points,
+            },
+            BoxCollider::new(size.x, size.y),
+            collision.layers(CollisionLayer::Pickup),
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::srgb(1.0, 0.8, 0.0), // Gold coins
+                    custom_size: Some(size),
+                    ..default()
+                },
+                transform: Transform::from_translation(collectible.position.extend(0.0)),
+                ..default()
+            },
+        ));
+    }
+
+    if let Some(tilemap) = &level.tilemap {
+        spawn_tilemap(
+            commands,
+            id,
+            tilemap,
+            collision,
+            asset_server,
+            atlas_layouts,
+        );
+    }
+
+    if let Some(goal) = &level.goal {
+        commands.spawn((
+            LevelEntity(id),
+            LevelGoal,
+            BoxCollider::new(goal.size.x, goal.size.y),
+            collision.layers(CollisionLayer::Goal),
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::srgb(1.0, 0.85, 0.1), // Yellow flag
+                    custom_size: Some(goal.size),
+                    ..default()
+                },
+                transform: Transform::from_translation(goal.position.extend(-0.1)),
+                ..default()
+            },
+        ));
+    }
+}
+
+/// A ramp's solid area, centered like its box
+fn ramp_mesh(ramp: &RampData) -> Mesh {
+    let half = ramp.size / 2.0;
+    let positions = vec![
+        [-half.x, -half.y, 0.0],
+        [half.x, -half.y, 0.0],
+        [half.x, ramp.right_height - half.y, 0.0],
+        [-half.x, ramp.left_height - half.y, 0.0],
+    ];
+    let uvs: Vec<[f32; 2]> = positions
+        .iter()
+        .map(|[x, y, _]| [x / ramp.size.x + 0.5, 0.5 - y / ramp.size.y])
+        .collect();
+
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0.0, 0.0, 1.0]; 4])
+    .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+    .with_inserted_indices(Indices::U32(vec![0, 1, 2, 0, 2, 3]))
+}
+
+/// Spawns the tile sprites of a tilemap under a single [`Tilemap`] parent,
+/// plus invisible merged colliders for its solid and one-way tiles
+fn spawn_tilemap(
+    commands: &mut Commands,
+    id: AssetId<LevelData>,
+    tilemap: &TileMapData,
+    collision: &CollisionConfig,
+    asset_server: &AssetServer,
+    atlas_layouts: &mut Assets<TextureAtlasLayout>,
+) {
+    let atlas = tilemap.atlas.as_ref().map(|atlas| {
+        let layout =
+            TextureAtlasLayout::from_grid(atlas.tile_size, atlas.columns, atlas.rows, None, None);
+        (
+            asset_server.load(atlas.image.clone()),
+            atlas_layouts.add(layout),
+        )
+    });
+
+    commands
+        .spawn((LevelEntity(id), Tilemap, SpatialBundle::default()))
+        .with_children(|parent| {
+            for y in 0..tilemap.rows {
+                for x in 0..tilemap.columns {
+                    let Some(tile) = tilemap.tile(x, y) else {
+                        continue;
+                    };
+                    let transform =
+                        Transform::from_translation(tilemap.cell_center(x, y).extend(-0.2));
+
+                    match &atlas {
+                        Some((image, layout)) => {
+                            let index = tilemap.cells[(y * tilemap.columns + x) as usize] - 1;
+                            parent.spawn((
+                                SpriteBundle {
+                                    sprite: Sprite {
+                                        custom_size: Some(tilemap.tile_size),
+                                        ..default()
+                                    },
+                                    texture: image.clone(),
+                                    transform,
+                                    ..default()
+                                },
+                                TextureAtlas {
+                                    layout: layout.clone(),
+                                    index: tile.atlas_index.unwrap_or(index as usize),
+                                },
+                            ));
+                        }
+                        None => {
+                            let [r, g, b] = tile.color.unwrap_or([0.3, 0.5, 0.3]);
+                            parent.spawn(SpriteBundle {
+                                sprite: Sprite {
+                                    color: Color::srgb(r, g, b),
+                                    custom_size: Some(tilemap.tile_size),
+                                    ..default()
+                                },
+                                transform,
+                                ..default()
+                            });
+                        }
+                    }
+                }
+            }
+        });
+
+    for (platforms, one_way) in [
+        (tilemap.solid_platforms(), false),
+        (tilemap.one_way_platforms(), true),
+    ] {
+        for platform in platforms {
+            let mut collider = commands.spawn((
+                LevelEntity(id),
+                Platform,
+                BoxCollider::new(platform.size.x, platform.size.y),
+                collision.layers(CollisionLayer::Platform),
+                TransformBundle::from_transform(Transform::from_translation(
+                    platform.position.extend(0.0),
+                )),
+            ));
+            if one_way {
+                collider.insert(OneWayPlatform);
+            }
+        }
+    }
+
+    for ladder in tilemap.ladders() {
+        commands.spawn((
+            trigger_volume(id, ladder.size),
+            Climbable,
+            TransformBundle::from_transform(Transform::from_translation(
+                ladder.position.extend(0.0),
+            )),
+        ));
+    }
+}
+
+/// An area that only triggers behaviour, such as a ladder or water, so it
+/// is kept out of the collision layers
+fn trigger_volume(id: AssetId<LevelData>, size: Vec2) -> impl Bundle {
+    (
+        LevelEntity(id),
+        BoxCollider::new(size.x, size.y),
+        CollisionLayers::new(&[], &[]),
+    )
+}
+
+/// Advances the game timer while the level is being played
+pub fn tick_game_timer(time: Res<Time>, mut timer: ResMut<GameTimer>) {
+    timer.tick(time.delta_seconds());
+}
+
+/// Ends the level when the player touches the level goal
+#[allow(clippy::too_many_arguments)]
+pub fn check_level_goal(
+    mut collisions: EventReader<Collision>,
+    player_query: Query<&Health, With<Player>>,
+    goal_query: Query<(), With<LevelGoal>>,
+    active_level: Res<ActiveLevel>,
+    levels: Res<Assets<LevelData>>,
+    config: Res<GameConfig>,
+    mut timer: ResMut<GameTimer>,
+    mut score: ResMut<Score>,
+    mut results: ResMut<LevelResults>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let Some(level) = levels.get(&active_level.handle) else {
+        collisions.clear();
+        return;
+    };
+
+    for collision in collisions.read() {
+        let Some((player, goal)) = collision.pair_with(|entity| player_query.contains(entity))
+        else {
+            continue;
+        };
+        let (Ok(health), true) = (player_query.get(player), goal_query.contains(goal)) else {
+            continue;
+        };
+
+        timer.pause();
+        *results = LevelResults::tally(level, &timer, health, &config.scoring);
+        score.add(results.total_bonus());
+        next_state.set(GameState::LevelComplete);
+        return;
+    }
+}
+
+/// Spawns the results screen showing the completion bonus
+pub fn spawn_results_screen(
+    mut commands: Commands,
+    results: Res<LevelResults>,
+    level_list: Res<LevelList>,
+    score: Res<Score>,
+) {
+    let prompt = if level_list.has_next() {
+        "Press ENTER for the next level"
+    } else {
+        "All levels complete! Press ENTER"
+    };
+
+    let lines = [
+        (format!("{} complete!", results.level_name), 48.0),
+        (
+            format!(
+                "Time left: {:.0}s  +{}",
+                results.time_remaining, results.time_bonus
+            ),
+            28.0,
+        ),
+        (
+            format!(
+                "Health left: {:.0}  +{}",
+                results.health_remaining, results.health_bonus
+            ),
+            28.0,
+        ),
+        (format!("Score: {}", score.current), 32.0),
+        (prompt.to_string(), 24.0),
+    ];
+
+    commands
+        .spawn((
+            ResultsScreen,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(12.0),
+                    ..default()
+                },
+                background_color: Color::srgba(0.0, 0.0, 0.0, 0.7).into(),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            for (text, font_size) in lines {
+                parent.spawn(TextBundle::from_section(
+                    text,
+                    TextStyle {
+                        font_size,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                ));
+            }
+        });
+}
+
+/// Despawns the results screen
+pub fn despawn_results_screen(mut commands: Commands, query: Query<Entity, With<ResultsScreen>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Waits for confirmation on the results screen, then moves to the next level
+pub fn advance_level(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut level_list: ResMut<LevelList>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::Enter) {
+        return;
+    }
+
+    if level_list.advance() {
+        next_state.set(GameState::Playing);
+    } else {
+        next_state.set(GameState::GameOver);
+    }
+}
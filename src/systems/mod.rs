@@ -4,20 +4,42 @@
 //! Systems are functions that run on entities with specific components.
 
 mod auto_movement;
+mod bounds;
 mod camera;
 mod collision;
+mod config;
+mod difficulty;
+mod level;
 mod movement;
 mod obstacle;
 mod setup;
 mod ui;
+mod water;
 
 // Re-export specific systems for clarity
-pub use auto_movement::apply_auto_movement;
-pub use camera::{camera_follow_system, setup_camera_follow};
-pub use collision::check_obstacle_collisions;
+pub use auto_movement::{
+    apply_auto_movement, apply_orbits, apply_sine_bob, bounce_obstacles, follow_spline_paths,
+    patrol_enemies, steer_homing,
+};
+pub use bounds::{check_kill_zones, check_level_bounds, despawn_out_of_bounds};
+pub use camera::{camera_follow_system, setup_camera_follow, update_camera_view};
+pub use collision::{
+    apply_crush_damage, check_collectible_pickups, check_enemy_collisions,
+    check_obstacle_collisions, check_tile_hazards, detect_collisions, detect_crushing,
+    turn_colliding_enemies, update_pressure_plates, update_spatial_hash, update_triggers,
+};
+pub use config::{apply_config, apply_config_to_entities, load_config};
+pub use difficulty::update_difficulty;
+pub use level::{
+    advance_level, check_level_goal, check_level_transition, check_levels_loaded,
+    despawn_results_screen, load_current_level, load_levels, reload_levels, spawn_level,
+    spawn_results_screen, stream_levels, tick_game_timer,
+};
 pub use movement::{
-    apply_gravity, apply_velocity, check_platform_collisions, player_jump, player_movement,
+    apply_gravity, apply_velocity, check_platform_collisions, player_climb, player_drop_through,
+    player_jump, player_movement, push_bodies, register_dynamic_bodies,
 };
-pub use obstacle::{despawn_offscreen_obstacles, spawn_obstacles};
-pub use setup::{setup_camera, spawn_platforms, spawn_player};
+pub use obstacle::spawn_obstacles;
+pub use setup::{setup_camera, spawn_player};
 pub use ui::{spawn_game_ui, update_health_bar, update_score_display};
+pub use water::{apply_swim_physics, update_breath, update_submerged};
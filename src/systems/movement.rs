@@ -3,18 +3,41 @@
 use bevy::prelude::*;
 
 use crate::components::{
-    BoxCollider, Gravity, Grounded, JumpConfig, Platform, Player, Speed, Velocity,
+    BoxCollider, Climbable, Collider, CollisionLayers, DropThrough, DynamicBody, Gravity,
+    GroundSurface, Grounded, JumpConfig, KinematicBody, MaxSlope, MovementMode, OneWayPlatform,
+    Platform, Player, Pushable, Slope, Speed, Submerged, SurfaceMaterial, Velocity,
 };
+use crate::resources::SpatialHash;
 
 /// Threshold for detecting landing on platforms (in pixels)
 const LANDING_THRESHOLD: f32 = 10.0;
+/// Distance below a shaped collider checked for ground, so a body resting
+/// exactly on a platform stays grounded
+const GROUND_PROBE: f32 = 1.0;
+/// How far a body walking downhill may leave a ramp's surface in one frame
+/// and still be snapped back onto it (in pixels)
+const SLOPE_SNAP_DISTANCE: f32 = 16.0;
+/// Slowest bounce off a bouncy surface; slower landings come to rest
+const MIN_BOUNCE_SPEED: f32 = 60.0;
 
-/// Handles player horizontal movement input (A/D or Left/Right arrows)
+/// Handles player horizontal movement input (A/D or Left/Right arrows).
+/// On the ground the [`GroundSurface`] decides how quickly the player
+/// reaches that speed, and water slows it down.
+#[allow(clippy::type_complexity)]
 pub fn player_movement(
+    time: Res<Time>,
     keyboard_input: Res<ButtonInput<KeyCode>>,
-    mut query: Query<(&Speed, &mut Velocity), With<Player>>,
+    mut query: Query<
+        (
+            &Speed,
+            &mut Velocity,
+            Option<&GroundSurface>,
+            Option<&Submerged>,
+        ),
+        With<Player>,
+    >,
 ) {
-    for (speed, mut velocity) in query.iter_mut() {
+    for (speed, mut velocity, surface, submerged) in query.iter_mut() {
         let mut direction = 0.0;
 
         if keyboard_input.pressed(KeyCode::KeyA) || keyboard_input.pressed(KeyCode::ArrowLeft) {
@@ -24,19 +47,26 @@ pub fn player_movement(
             direction += 1.0;
         }
 
-        velocity.0.x = direction * speed.0;
+        let surface = surface.map(|surface| surface.0).unwrap_or_default();
+        let speed = speed.0 * submerged.map_or(1.0, Submerged::speed_scale);
+        velocity.0.x = surface.steer(velocity.0.x, direction * speed, time.delta_seconds());
     }
 }
 
-/// Handles player jump input (Spacebar) - only when grounded
+/// Handles player jump input (Spacebar) - only when grounded, or as a
+/// swim stroke any number of times while swimming
 pub fn player_jump(
     keyboard_input: Res<ButtonInput<KeyCode>>,
-    mut query: Query<(&mut Velocity, &Grounded, &JumpConfig), With<Player>>,
+    mut query: Query<(&mut Velocity, &Grounded, &JumpConfig, Option<&MovementMode>), With<Player>>,
 ) {
-    for (mut velocity, grounded, jump_config) in query.iter_mut() {
-        // Jump when spacebar is pressed and player is on the ground
-        if keyboard_input.just_pressed(KeyCode::Space) && grounded.0 {
-            velocity.0.y = jump_config.jump_velocity;
+    for (mut velocity, grounded, jump_config, mode) in query.iter_mut() {
+        if keyboard_input.just_pressed(KeyCode::Space) {
+            if mode == Some(&MovementMode::Swimming) {
+                velocity.0.y = jump_config.stroke_velocity;
+            } else if grounded.0 {
+                // Jump when spacebar is pressed and player is on the ground
+                velocity.0.y = jump_config.jump_velocity;
+            }
         }
 
         // Variable jump height: cut velocity when spacebar is released mid-jump
@@ -46,73 +76,549 @@ pub fn player_jump(
     }
 }
 
-/// Applies gravity to entities with Gravity and Velocity components
-pub fn apply_gravity(time: Res<Time>, mut query: Query<(&Gravity, &mut Velocity, &Grounded)>) {
-    for (gravity, mut velocity, grounded) in query.iter_mut() {
-        if !grounded.0 {
+/// Handles dropping through one-way platforms while holding down (S or Down arrow)
+pub fn player_drop_through(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut query: Query<&mut DropThrough, With<Player>>,
+) {
+    for mut drop_through in query.iter_mut() {
+        drop_through.0 =
+            keyboard_input.pressed(KeyCode::KeyS) || keyboard_input.pressed(KeyCode::ArrowDown);
+    }
+}
+
+/// Enters and leaves climbing while the player overlaps a [`Climbable`].
+///
+/// Holding up (W or Up arrow) or down (S or Down arrow) on a climbable
+/// grabs it, after which those keys move the player vertically at its
+/// [`Speed`]. Jumping or leaving the climbable lets go.
+#[allow(clippy::type_complexity)]
+pub fn player_climb(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut query: Query<
+        (
+            &Transform,
+            &BoxCollider,
+            &Speed,
+            &JumpConfig,
+            &mut Velocity,
+            &mut MovementMode,
+        ),
+        With<Player>,
+    >,
+    climbables: Query<(&Transform, &BoxCollider), (With<Climbable>, Without<Player>)>,
+    spatial_hash: Res<SpatialHash>,
+) {
+    for (transform, collider, speed, jump_config, mut velocity, mut mode) in query.iter_mut() {
+        let bounds = collider.bounds(transform.translation);
+        // Standing right on top of a ladder still reaches it
+        let reach = bounds.inflate(GROUND_PROBE);
+        let on_climbable = climbables.iter_many(spatial_hash.query(reach)).any(
+            |(climbable_transform, climbable_collider)| {
+                !climbable_collider
+                    .bounds(climbable_transform.translation)
+                    .intersect(reach)
+                    .is_empty()
+            },
+        );
+
+        let mut direction = 0.0;
+        if keyboard_input.pressed(KeyCode::KeyW) || keyboard_input.pressed(KeyCode::ArrowUp) {
+            direction += 1.0;
+        }
+        if keyboard_input.pressed(KeyCode::KeyS) || keyboard_input.pressed(KeyCode::ArrowDown) {
+            direction -= 1.0;
+        }
+
+        match *mode {
+            MovementMode::Normal if on_climbable && direction != 0.0 => {
+                *mode = MovementMode::Climbing;
+            }
+            MovementMode::Climbing if !on_climbable => {
+                *mode = MovementMode::Normal;
+                continue;
+            }
+            MovementMode::Climbing if keyboard_input.just_pressed(KeyCode::Space) => {
+                *mode = MovementMode::Normal;
+                velocity.0.y = jump_config.jump_velocity;
+                continue;
+            }
+            _ => {}
+        }
+
+        if *mode == MovementMode::Climbing {
+            velocity.0.y = direction * speed.0;
+        }
+    }
+}
+
+/// Makes every entity with [`Velocity`], a [`BoxCollider`] and [`Gravity`]
+/// a [`DynamicBody`], unless it is a [`KinematicBody`], giving it
+/// [`Grounded`] if it has none
+#[allow(clippy::type_complexity)]
+pub fn register_dynamic_bodies(
+    mut commands: Commands,
+    query: Query<
+        (Entity, Has<Grounded>),
+        (
+            With<Velocity>,
+            With<BoxCollider>,
+            With<Gravity>,
+            Without<KinematicBody>,
+            Or<(Without<DynamicBody>, Without<Grounded>)>,
+        ),
+    >,
+) {
+    for (entity, has_grounded) in query.iter() {
+        let mut entity = commands.entity(entity);
+        entity.insert(DynamicBody);
+        if !has_grounded {
+            entity.insert(Grounded::default());
+        }
+    }
+}
+
+/// Applies gravity to [`DynamicBody`]s, except while their
+/// [`MovementMode`] has none. Under water
+/// [`apply_swim_physics`](super::water::apply_swim_physics) takes over.
+#[allow(clippy::type_complexity)]
+pub fn apply_gravity(
+    time: Res<Time>,
+    mut query: Query<
+        (&Gravity, &mut Velocity, &Grounded, Option<&MovementMode>),
+        (With<DynamicBody>, Without<Submerged>),
+    >,
+) {
+    for (gravity, mut velocity, grounded, mode) in query.iter_mut() {
+        if !grounded.0 && mode.is_none_or(|mode| mode.has_gravity()) {
             velocity.0.y -= gravity.0 * time.delta_seconds();
         }
     }
 }
 
-/// Applies velocity to entity transforms
-pub fn apply_velocity(time: Res<Time>, mut query: Query<(&Velocity, &mut Transform)>) {
-    for (velocity, mut transform) in query.iter_mut() {
-        transform.translation.x += velocity.0.x * time.delta_seconds();
+/// Applies velocity to entity transforms. Grounded entities are also
+/// carried along by a conveyor [`GroundSurface`].
+pub fn apply_velocity(
+    time: Res<Time>,
+    mut query: Query<(
+        &Velocity,
+        &mut Transform,
+        Option<&Grounded>,
+        Option<&GroundSurface>,
+    )>,
+) {
+    for (velocity, mut transform, grounded, surface) in query.iter_mut() {
+        let conveyor = match (grounded, surface) {
+            (Some(Grounded(true)), Some(surface)) => surface.0.conveyor,
+            _ => 0.0,
+        };
+        transform.translation.x += (velocity.0.x + conveyor) * time.delta_seconds();
         transform.translation.y += velocity.0.y * time.delta_seconds();
     }
 }
 
-/// Checks for collisions between player and platforms
+/// Resolves every [`DynamicBody`] against platforms.
+///
+/// Boxes land on platform tops. When either side has a round or rotated
+/// [`Collider`], the shapes are pushed apart along the contact normal
+/// instead, so a capsule hanging over a ledge slides off it. Ramps
+/// ([`Slope`]) hold a body on their surface up to its [`MaxSlope`].
+/// The [`SurfaceMaterial`] stood on is recorded in [`GroundSurface`], and
+/// bouncy ones launch the body back up. A body that is also a platform is
+/// stood on by others but never by itself.
 #[allow(clippy::type_complexity)]
 pub fn check_platform_collisions(
-    mut player_query: Query<
-        (&mut Transform, &mut Velocity, &BoxCollider, &mut Grounded),
-        With<Player>,
-    >,
-    platform_query: Query<(&Transform, &BoxCollider), (With<Platform>, Without<Player>)>,
+    mut queries: ParamSet<(
+        Query<
+            (
+                Entity,
+                &mut Transform,
+                &mut Velocity,
+                &BoxCollider,
+                &mut Grounded,
+                Option<&mut GroundSurface>,
+                Option<&DropThrough>,
+                Option<&MaxSlope>,
+                Option<&Collider>,
+                Option<&CollisionLayers>,
+            ),
+            With<DynamicBody>,
+        >,
+        Query<
+            (
+                Entity,
+                &Transform,
+                &BoxCollider,
+                Has<OneWayPlatform>,
+                Option<&Slope>,
+                Option<&SurfaceMaterial>,
+                Option<&Collider>,
+                Option<&CollisionLayers>,
+            ),
+            With<Platform>,
+        >,
+    )>,
+    spatial_hash: Res<SpatialHash>,
 ) {
-    for (mut player_transform, mut velocity, player_collider, mut grounded) in
-        player_query.iter_mut()
-    {
+    let bodies: Vec<(Entity, Rect)> = queries
+        .p0()
+        .iter()
+        .map(|(entity, transform, _, collider, ..)| {
+            (entity, collider.bounds(transform.translation))
+        })
+        .collect();
+
+    for (body, bounds) in bodies {
+        // Wide enough to land on platforms and to snap down onto ramps
+        let area = bounds.inflate(LANDING_THRESHOLD.max(SLOPE_SNAP_DISTANCE));
+        let nearby = spatial_hash.query(area);
+        // Copied out, since moving platforms can be bodies themselves
+        let platforms: Vec<_> = queries
+            .p1()
+            .iter_many(&nearby)
+            .filter(|(platform, ..)| *platform != body)
+            .map(
+                |(_, transform, collider, one_way, slope, material, shape, layers)| {
+                    (
+                        *transform,
+                        collider.clone(),
+                        one_way,
+                        slope.copied(),
+                        material.copied(),
+                        shape.copied(),
+                        layers.copied(),
+                    )
+                },
+            )
+            .collect();
+
+        let mut bodies = queries.p0();
+        let Ok((
+            _,
+            mut body_transform,
+            mut velocity,
+            body_collider,
+            mut grounded,
+            ground_surface,
+            drop_through,
+            max_slope,
+            body_shape,
+            layers,
+        )) = bodies.get_mut(body)
+        else {
+            continue;
+        };
+        let layers = layers.copied().unwrap_or_default();
+        let dropping = drop_through.is_some_and(|drop_through| drop_through.0);
+        let max_slope = max_slope.copied().unwrap_or_default().0;
+        let was_grounded = grounded.0;
+        let landing_speed = -velocity.0.y;
         let mut is_grounded = false;
+        let mut surface = SurfaceMaterial::default();
+        // Surface just below a body that was grounded, applied only if
+        // nothing else holds it up
+        let mut snap_height = None;
+
+        let body_half_width = body_collider.width / 2.0;
+        let body_half_height = body_collider.height / 2.0;
+
+        for (
+            platform_transform,
+            platform_collider,
+            one_way,
+            slope,
+            material,
+            platform_shape,
+            platform_layers,
+        ) in platforms
+        {
+            let material = material.unwrap_or_default();
+            if !layers.interacts_with(&platform_layers.unwrap_or_default()) {
+                continue;
+            }
+
+            // Fall through one-way platforms while dropping
+            if one_way && dropping {
+                continue;
+            }
+
+            if let Some(slope) = &slope {
+                let standing = resolve_slope(
+                    &mut body_transform,
+                    &mut velocity,
+                    body_half_height,
+                    was_grounded,
+                    max_slope,
+                    platform_collider.bounds(platform_transform.translation),
+                    slope,
+                    &mut snap_height,
+                    material,
+                );
+                if standing {
+                    is_grounded = true;
+                    surface = material;
+                }
+                continue;
+            }
 
-        let player_half_width = player_collider.width / 2.0;
-        let player_half_height = player_collider.height / 2.0;
+            if is_shaped(body_shape) || is_shaped(platform_shape.as_ref()) {
+                let body_shape = body_shape
+                    .copied()
+                    .unwrap_or_else(|| Collider::from(body_collider));
+                let platform_shape =
+                    platform_shape.unwrap_or_else(|| Collider::from(&platform_collider));
+                let standing = resolve_shape_contact(
+                    &mut body_transform,
+                    &mut velocity,
+                    &body_shape,
+                    platform_transform.translation.truncate(),
+                    &platform_shape,
+                    one_way,
+                    max_slope,
+                );
+                if standing {
+                    is_grounded = true;
+                    surface = material;
+                }
+                continue;
+            }
 
-        for (platform_transform, platform_collider) in platform_query.iter() {
             let platform_half_width = platform_collider.width / 2.0;
             let platform_half_height = platform_collider.height / 2.0;
 
             // Calculate AABB bounds
-            let player_left = player_transform.translation.x - player_half_width;
-            let player_right = player_transform.translation.x + player_half_width;
-            let player_bottom = player_transform.translation.y - player_half_height;
-            let player_top = player_transform.translation.y + player_half_height;
+            let body_left = body_transform.translation.x - body_half_width;
+            let body_right = body_transform.translation.x + body_half_width;
+            let body_bottom = body_transform.translation.y - body_half_height;
+            let body_top = body_transform.translation.y + body_half_height;
 
             let platform_left = platform_transform.translation.x - platform_half_width;
             let platform_right = platform_transform.translation.x + platform_half_width;
             let platform_top = platform_transform.translation.y + platform_half_height;
 
             // Check for horizontal overlap
-            let horizontal_overlap = player_left < platform_right && player_right > platform_left;
+            let horizontal_overlap = body_left < platform_right && body_right > platform_left;
 
-            // Check if player is landing on top of platform (falling down)
+            // Check if body is landing on top of platform (falling down)
             if horizontal_overlap && velocity.0.y <= 0.0 {
-                // Check if player's bottom is at or slightly below platform top
-                // and player was above platform before
-                if player_bottom <= platform_top
-                    && player_bottom >= platform_top - LANDING_THRESHOLD
-                    && player_top > platform_top
+                // Check if body's bottom is at or slightly below platform top
+                // and body was above platform before
+                if body_bottom <= platform_top
+                    && body_bottom >= platform_top - LANDING_THRESHOLD
+                    && body_top > platform_top
                 {
                     // Land on platform
-                    player_transform.translation.y = platform_top + player_half_height;
+                    body_transform.translation.y = platform_top + body_half_height;
                     velocity.0.y = 0.0;
                     is_grounded = true;
+                    surface = material;
+                } else if was_grounded
+                    && body_bottom > platform_top
+                    && body_bottom <= platform_top + SLOPE_SNAP_DISTANCE
+                {
+                    // Stepped off the low end of a ramp
+                    snap_to_highest(&mut snap_height, platform_top + body_half_height, material);
                 }
             }
         }
 
+        if let Some((height, material)) = snap_height.filter(|_| !is_grounded) {
+            body_transform.translation.y = height;
+            velocity.0.y = 0.0;
+            is_grounded = true;
+            surface = material;
+        }
+
+        // Bounce pads throw the body back up until the bounces die down
+        let bounce_speed = landing_speed * surface.bounciness;
+        if is_grounded && bounce_speed > MIN_BOUNCE_SPEED {
+            velocity.0.y = bounce_speed;
+            is_grounded = false;
+        }
+
         grounded.0 = is_grounded;
+        if let Some(mut ground_surface) = ground_surface {
+            ground_surface.0 = if is_grounded {
+                surface
+            } else {
+                SurfaceMaterial::default()
+            };
+        }
+    }
+}
+
+/// Lets the player shove [`Pushable`] bodies they walk into. The body moves
+/// at its [`Pushable::push_speed`] and the player is held against it, so
+/// both move at the body's pace. Grounded bodies stop once nobody pushes
+/// them.
+#[allow(clippy::type_complexity)]
+pub fn push_bodies(
+    mut pushers: Query<(&mut Transform, &Velocity, &BoxCollider), With<Player>>,
+    mut pushables: Query<
+        (
+            &Transform,
+            &BoxCollider,
+            &Pushable,
+            &mut Velocity,
+            &Grounded,
+        ),
+        Without<Player>,
+    >,
+) {
+    for (transform, collider, pushable, mut velocity, grounded) in pushables.iter_mut() {
+        let bounds = collider.bounds(transform.translation);
+        let mut pushed = None;
+
+        for (mut pusher_transform, pusher_velocity, pusher_collider) in pushers.iter_mut() {
+            let pusher_bounds = pusher_collider.bounds(pusher_transform.translation);
+            let overlap = bounds.intersect(pusher_bounds);
+            // Only walking into a side pushes, standing on top does not
+            if overlap.is_empty() || overlap.width() >= overlap.height() {
+                continue;
+            }
+            let direction = (bounds.center().x - pusher_bounds.center().x).signum();
+            if pusher_velocity.0.x * direction <= 0.0 {
+                continue;
+            }
+
+            pushed = Some(direction * pushable.push_speed(pusher_velocity.0.x.abs()));
+            let half_width = pusher_collider.width / 2.0;
+            pusher_transform.translation.x = if direction > 0.0 {
+                bounds.min.x - half_width
+            } else {
+                bounds.max.x + half_width
+            };
+        }
+
+        match pushed {
+            Some(speed) => velocity.0.x = speed,
+            None if grounded.0 => velocity.0.x = 0.0,
+            None => {}
+        }
+    }
+}
+
+fn snap_to_highest(
+    snap_height: &mut Option<(f32, SurfaceMaterial)>,
+    height: f32,
+    material: SurfaceMaterial,
+) {
+    if snap_height.is_none_or(|(current, _)| height > current) {
+        *snap_height = Some((height, material));
+    }
+}
+
+/// Whether `shape` needs the contact solver rather than box landing
+fn is_shaped(shape: Option<&Collider>) -> bool {
+    shape.is_some_and(|shape| !matches!(shape, Collider::Aabb { .. }))
+}
+
+/// Pushes `shape` out of a platform along the contact normal and removes
+/// the velocity into the platform, keeping the part along its surface.
+/// Returns whether the platform can be stood on.
+fn resolve_shape_contact(
+    transform: &mut Transform,
+    velocity: &mut Velocity,
+    shape: &Collider,
+    platform_position: Vec2,
+    platform_shape: &Collider,
+    one_way: bool,
+    max_slope: f32,
+) -> bool {
+    let probe = transform.translation.truncate() - Vec2::Y * GROUND_PROBE;
+    let Some(contact) = shape.contact(probe, platform_shape, platform_position) else {
+        return false;
+    };
+    let into_surface = velocity.0.dot(contact.normal);
+    // Only the probe touches and the body is moving away, e.g. jumping
+    if into_surface > 0.0 && contact.depth <= GROUND_PROBE {
+        return false;
+    }
+    let standing = contact.normal.y >= max_slope.cos();
+
+    // One-way platforms only catch bodies falling onto their top
+    if one_way
+        && !(standing && velocity.0.y <= 0.0 && contact.depth <= LANDING_THRESHOLD + GROUND_PROBE)
+    {
+        return false;
+    }
+
+    let mut resolved = probe + contact.normal * contact.depth;
+    if !standing {
+        // Walls and ceilings should not drag the body down by the probe
+        resolved.y += GROUND_PROBE;
+    }
+    transform.translation = resolved.extend(transform.translation.z);
+
+    if into_surface < 0.0 {
+        velocity.0 -= contact.normal * into_surface;
+    }
+    standing
+}
+
+/// Keeps a body on a ramp's top edge, measured under the middle of its feet.
+/// Walkable ramps hold it like a flat platform, and while it walks downhill
+/// offer their surface in `snap_height`; steeper ones push it back downhill
+/// and only slow its fall, so it slides. Returns whether the body stands on
+/// the ramp.
+#[allow(clippy::too_many_arguments)]
+fn resolve_slope(
+    transform: &mut Transform,
+    velocity: &mut Velocity,
+    half_height: f32,
+    was_grounded: bool,
+    max_slope: f32,
+    ramp: Rect,
+    slope: &Slope,
+    snap_height: &mut Option<(f32, SurfaceMaterial)>,
+    material: SurfaceMaterial,
+) -> bool {
+    let foot = Vec2::new(
+        transform.translation.x,
+        transform.translation.y - half_height,
+    );
+    if velocity.0.y > 0.0 || !(ramp.min.x..=ramp.max.x).contains(&foot.x) {
+        return false;
+    }
+
+    let angle = slope.angle(ramp.width());
+    let surface = slope.surface_at(foot.x, ramp);
+    let depth = surface - foot.y;
+    // Steep ramps are entered from the side, so allow for the same
+    // horizontal overlap as a vertical landing on flat ground
+    let catch = LANDING_THRESHOLD * angle.tan().max(1.0);
+    let snap = if was_grounded {
+        SLOPE_SNAP_DISTANCE
+    } else {
+        0.0
+    };
+    if depth > catch || depth < -snap {
+        return false;
+    }
+
+    if angle <= max_slope {
+        if depth < 0.0 {
+            snap_to_highest(snap_height, surface + half_height, material);
+            return false;
+        }
+        transform.translation.y = surface + half_height;
+        velocity.0.y = 0.0;
+        return true;
+    }
+    if depth <= 0.0 {
+        return false;
+    }
+
+    let downhill = if slope.right_height > slope.left_height {
+        -1.0
+    } else {
+        1.0
+    };
+    transform.translation.x += downhill * depth / angle.tan();
+    let normal = Vec2::new(downhill * angle.sin(), angle.cos());
+    let into_surface = velocity.0.dot(normal);
+    if into_surface < 0.0 {
+        velocity.0 -= normal * into_surface;
     }
+    false
 }
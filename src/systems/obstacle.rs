@@ -1,64 +1,200 @@
 //! Obstacle spawning systems
 
+use bevy::ecs::system::EntityCommands;
 use bevy::prelude::*;
+use rand::seq::SliceRandom;
 use rand::Rng;
 
-use crate::components::{AutoMove, BoxCollider, DamageOnContact, Obstacle};
-use crate::game::constants::obstacles::{
-    DESPAWN_X, HEIGHT_MAX, HEIGHT_MIN, SPAWN_X, SPAWN_Y_MAX, SPAWN_Y_MIN, SPEED_MAX, SPEED_MIN,
-    WIDTH_MAX, WIDTH_MIN,
+use crate::components::{
+    AutoMove, Bounce, BoxCollider, Collider, CollisionLayer, CollisionLayers, DamageOnContact,
+    Homing, Obstacle, Orbit, ShapeKind, SineBob, SplinePath,
+};
+use crate::game::config::{GameConfig, ObstacleConfig, ScreenEdge};
+use crate::game::waves::{ObstacleMovement, SpawnPattern};
+use crate::resources::{
+    CameraView, Difficulty, EntityPool, GameRng, ObstacleSpawnTimer, RngStream, WaveState,
 };
-use crate::resources::ObstacleSpawnTimer;
 
-/// Spawns obstacles at regular intervals
+/// Spawns obstacles just outside the camera view, scaled by the current
+/// difficulty. Plays the configured waves, or spawns a random obstacle on the
+/// spawn timer when there are none.
+#[allow(clippy::too_many_arguments)]
 pub fn spawn_obstacles(
     mut commands: Commands,
     time: Res<Time>,
+    config: Res<GameConfig>,
+    camera_view: Res<CameraView>,
+    difficulty: Res<Difficulty>,
     mut spawn_timer: ResMut<ObstacleSpawnTimer>,
+    mut wave_state: ResMut<WaveState>,
+    mut pool: ResMut<EntityPool<Obstacle>>,
+    mut game_rng: ResMut<GameRng>,
 ) {
-    spawn_timer.timer.tick(time.delta());
+    let obstacles = config.obstacles.scaled(&difficulty);
+    let layers = config.collision.layers(CollisionLayer::Obstacle);
+
+    if config.waves.waves.is_empty() {
+        spawn_timer.timer.tick(time.delta());
+        if spawn_timer.timer.just_finished() {
+            spawn_random_obstacle(
+                &mut commands,
+                &mut pool,
+                &obstacles,
+                layers,
+                &camera_view,
+                game_rng.stream(RngStream::Obstacles),
+            );
+        }
+        return;
+    }
 
-    if spawn_timer.timer.just_finished() {
-        let mut rng = rand::thread_rng();
+    // Formations are laid out from the right edge, level with the view center
+    let origin = camera_view.spawn_point(ScreenEdge::Right, obstacles.spawn_margin, 0.0);
 
-        // Random spawn position on the right side of the screen
-        let spawn_x = SPAWN_X;
-        let spawn_y = rng.gen_range(SPAWN_Y_MIN..SPAWN_Y_MAX);
+    // Wave delays shrink as the spawn rate ramps up
+    let delta = time.delta_seconds() * difficulty.spawn_rate;
+    let due = wave_state.advance(delta, &config.waves, game_rng.stream(RngStream::Waves));
+    for (wave, entry) in due {
+        let pattern = &config.waves.waves[wave].entries[entry].pattern;
+        if *pattern == SpawnPattern::Random {
+            spawn_random_obstacle(
+                &mut commands,
+                &mut pool,
+                &obstacles,
+                layers,
+                &camera_view,
+                game_rng.stream(RngStream::Obstacles),
+            );
+            continue;
+        }
 
-        // Random obstacle size
-        let width = rng.gen_range(WIDTH_MIN..WIDTH_MAX);
-        let height = rng.gen_range(HEIGHT_MIN..HEIGHT_MAX);
+        for placement in config.waves.placements(pattern) {
+            let kind = placement.kind;
+            let size = kind.size * difficulty.size;
+            let [r, g, b] = kind.color.unwrap_or(OBSTACLE_COLOR);
+            let mut obstacle = spawn_obstacle(
+                &mut commands,
+                &mut pool,
+                origin + placement.position,
+                size,
+                kind.damage.unwrap_or(config.obstacles.damage) * difficulty.damage,
+                Color::srgb(r, g, b),
+                layers,
+            );
+            if kind.shape != ShapeKind::Box {
+                obstacle.insert(Collider::fitted(kind.shape, size));
+            }
+            insert_movement(&mut obstacle, &kind.movement, kind.speed * difficulty.speed);
+        }
+    }
+}
 
-        // Random speed
-        let speed = rng.gen_range(SPEED_MIN..SPEED_MAX);
+/// Red, used for obstacles without a color of their own
+const OBSTACLE_COLOR: [f32; 3] = [0.8, 0.2, 0.2];
 
-        commands.spawn((
-            Obstacle,
-            AutoMove::left(speed),
-            BoxCollider::new(width, height),
-            DamageOnContact::default(),
-            SpriteBundle {
-                sprite: Sprite {
-                    color: Color::srgb(0.8, 0.2, 0.2), // Red obstacles
-                    custom_size: Some(Vec2::new(width, height)),
-                    ..default()
-                },
-                transform: Transform::from_xyz(spawn_x, spawn_y, 0.0),
+/// Spawns one obstacle off a random edge with a random position, size and
+/// speed, heading into the view
+fn spawn_random_obstacle(
+    commands: &mut Commands,
+    pool: &mut EntityPool<Obstacle>,
+    obstacles: &ObstacleConfig,
+    layers: CollisionLayers,
+    camera_view: &CameraView,
+    rng: &mut impl Rng,
+) {
+    // Random spawn position along one of the configured edges
+    let edge = *obstacles
+        .spawn_edges
+        .choose(rng)
+        .unwrap_or(&ScreenEdge::Right);
+    let along = match edge {
+        ScreenEdge::Left | ScreenEdge::Right => {
+            rng.gen_range(obstacles.spawn_y_min..obstacles.spawn_y_max)
+        }
+        ScreenEdge::Top | ScreenEdge::Bottom => {
+            let half_width = camera_view.0.half_size().x;
+            rng.gen_range(-half_width..half_width)
+        }
+    };
+
+    // Random obstacle size
+    let width = rng.gen_range(obstacles.width_min..obstacles.width_max);
+    let height = rng.gen_range(obstacles.height_min..obstacles.height_max);
+
+    // Random speed
+    let speed = rng.gen_range(obstacles.speed_min..obstacles.speed_max);
+
+    let [r, g, b] = OBSTACLE_COLOR;
+    spawn_obstacle(
+        commands,
+        pool,
+        camera_view.spawn_point(edge, obstacles.spawn_margin, along),
+        Vec2::new(width, height),
+        obstacles.damage,
+        Color::srgb(r, g, b),
+        layers,
+    )
+    .insert(AutoMove::new(-edge.normal(), speed));
+}
+
+/// Spawns a stationary obstacle, reusing a pooled entity when there is one.
+/// Movement components are added by the caller.
+fn spawn_obstacle<'a>(
+    commands: &'a mut Commands,
+    pool: &mut EntityPool<Obstacle>,
+    position: Vec2,
+    size: Vec2,
+    damage: f32,
+    color: Color,
+    layers: CollisionLayers,
+) -> EntityCommands<'a> {
+    let mut obstacle = pool.acquire(commands);
+    obstacle.insert((
+        Obstacle,
+        BoxCollider::new(size.x, size.y),
+        layers,
+        DamageOnContact::new(damage),
+        SpriteBundle {
+            sprite: Sprite {
+                color,
+                custom_size: Some(size),
                 ..default()
             },
-        ));
-    }
+            transform: Transform::from_translation(position.extend(0.0)),
+            ..default()
+        },
+    ));
+    obstacle
 }
 
-/// Despawns obstacles that have moved off screen
-pub fn despawn_offscreen_obstacles(
-    mut commands: Commands,
-    query: Query<(Entity, &Transform), With<Obstacle>>,
-) {
-    for (entity, transform) in query.iter() {
-        // Despawn if too far left
-        if transform.translation.x < DESPAWN_X {
-            commands.entity(entity).despawn();
+/// Adds the components for an obstacle movement
+fn insert_movement(obstacle: &mut EntityCommands, movement: &ObstacleMovement, speed: f32) {
+    match movement {
+        ObstacleMovement::Straight => obstacle.insert(AutoMove::left(speed)),
+        ObstacleMovement::Sine {
+            amplitude,
+            frequency,
+        } => obstacle.insert((AutoMove::left(speed), SineBob::new(*amplitude, *frequency))),
+        ObstacleMovement::Homing { turn_rate } => {
+            obstacle.insert((AutoMove::left(speed), Homing::new(*turn_rate)))
         }
-    }
+        ObstacleMovement::Bounce {
+            gravity,
+            restitution,
+            launch_speed,
+        } => obstacle.insert((
+            AutoMove::left(speed),
+            Bounce {
+                velocity_y: *launch_speed,
+                ..Bounce::new(*gravity, *restitution)
+            },
+        )),
+        ObstacleMovement::Orbit {
+            radius,
+            angular_speed,
+        } => obstacle.insert((AutoMove::left(speed), Orbit::new(*radius, *angular_speed))),
+        ObstacleMovement::Spline { points, looping } => {
+            obstacle.insert(SplinePath::new(points.clone(), speed, *looping))
+        }
+    };
 }
@@ -3,9 +3,11 @@
 use bevy::prelude::*;
 
 use crate::components::{
-    BoxCollider, CameraFollow, Gravity, Grounded, Health, JumpConfig, MainCamera, Platform, Player,
-    Speed, Velocity,
+    BoxCollider, Breath, CameraFollow, Collider, CollisionLayer, DropThrough, DynamicBody, Gravity,
+    GroundSurface, Grounded, Health, JumpConfig, MainCamera, MaxSlope, MovementMode, Player,
+    ShapeKind, Speed, Velocity,
 };
+use crate::game::config::GameConfig;
 
 /// Spawns a 2D camera for the game with MainCamera marker
 pub fn setup_camera(mut commands: Commands) {
@@ -17,18 +19,29 @@ pub fn setup_camera(mut commands: Commands) {
 }
 
 /// Spawns the player entity with platformer components
-pub fn spawn_player(mut commands: Commands) {
+pub fn spawn_player(mut commands: Commands, config: Res<GameConfig>) {
     let player_size = Vec2::new(40.0, 50.0);
+    let player = &config.player;
 
-    commands.spawn((
+    let mut entity = commands.spawn((
         Player,
-        Speed(250.0),
-        Health::default(),
+        Speed(player.speed),
+        Health::new(player.max_health),
+        DynamicBody,
         Velocity::default(),
-        Gravity::default(),
+        Gravity(player.gravity),
         Grounded(false),
-        JumpConfig::default(),
+        DropThrough::default(),
+        JumpConfig {
+            jump_velocity: player.jump_velocity,
+            jump_cut_multiplier: player.jump_cut_multiplier,
+            stroke_velocity: player.swim_stroke_velocity,
+        },
+        MaxSlope(player.max_slope_angle.to_radians()),
+        GroundSurface::default(),
+        MovementMode::default(),
         BoxCollider::new(player_size.x, player_size.y),
+        config.collision.layers(CollisionLayer::Player),
         SpriteBundle {
             sprite: Sprite {
                 color: Color::srgb(0.2, 0.6, 1.0), // Blue player
@@ -39,49 +52,10 @@ pub fn spawn_player(mut commands: Commands) {
             ..default()
         },
     ));
-}
-
-/// Spawns the ground and platforms for the platformer
-pub fn spawn_platforms(mut commands: Commands) {
-    // Ground platform
-    let ground_width = 800.0;
-    let ground_height = 40.0;
-    commands.spawn((
-        Platform,
-        BoxCollider::new(ground_width, ground_height),
-        SpriteBundle {
-            sprite: Sprite {
-                color: Color::srgb(0.4, 0.3, 0.2), // Brown ground
-                custom_size: Some(Vec2::new(ground_width, ground_height)),
-                ..default()
-            },
-            transform: Transform::from_xyz(0.0, -250.0, 0.0),
-            ..default()
-        },
-    ));
-
-    // Floating platforms
-    let platform_configs = [
-        (-200.0, -100.0, 150.0, 20.0), // Left lower platform
-        (150.0, -50.0, 120.0, 20.0),   // Right lower platform
-        (-50.0, 50.0, 180.0, 20.0),    // Center middle platform
-        (250.0, 120.0, 100.0, 20.0),   // Right upper platform
-        (-250.0, 150.0, 100.0, 20.0),  // Left upper platform
-    ];
-
-    for (x, y, width, height) in platform_configs {
-        commands.spawn((
-            Platform,
-            BoxCollider::new(width, height),
-            SpriteBundle {
-                sprite: Sprite {
-                    color: Color::srgb(0.3, 0.5, 0.3), // Green platforms
-                    custom_size: Some(Vec2::new(width, height)),
-                    ..default()
-                },
-                transform: Transform::from_xyz(x, y, 0.0),
-                ..default()
-            },
-        ));
+    if let Some(breath) = player.breath {
+        entity.insert(Breath::new(breath, player.drowning_damage));
+    }
+    if player.shape != ShapeKind::Box {
+        entity.insert(Collider::fitted(player.shape, player_size));
     }
 }
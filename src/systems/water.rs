@@ -0,0 +1,110 @@
+//! Water volumes: swimming, drag and drowning
+
+use bevy::prelude::*;
+
+use crate::components::{
+    AutoMove, BoxCollider, Breath, DynamicBody, Gravity, Health, MovementMode, Patrol, Platform,
+    SplinePath, Submerged, Velocity, WaterVolume,
+};
+use crate::resources::SpatialHash;
+
+/// Marks movers whose center is inside a [`WaterVolume`] as [`Submerged`]
+/// and switches those with a [`MovementMode`] to swimming and back
+#[allow(clippy::type_complexity)]
+pub fn update_submerged(
+    mut commands: Commands,
+    mut bodies: Query<
+        (
+            Entity,
+            &Transform,
+            &BoxCollider,
+            Has<Submerged>,
+            Option<&mut MovementMode>,
+        ),
+        (
+            Or<(
+                With<Velocity>,
+                With<AutoMove>,
+                With<Patrol>,
+                With<SplinePath>,
+            )>,
+            Without<Platform>,
+            Without<WaterVolume>,
+        ),
+    >,
+    waters: Query<(&Transform, &BoxCollider, &WaterVolume)>,
+    spatial_hash: Res<SpatialHash>,
+) {
+    for (entity, transform, collider, was_submerged, mode) in bodies.iter_mut() {
+        let center = transform.translation.truncate();
+        let submerged = waters
+            .iter_many(spatial_hash.query(collider.bounds(transform.translation)))
+            .map(|(water_transform, water_collider, water)| {
+                (water_collider.bounds(water_transform.translation), water)
+            })
+            .find(|(bounds, _)| bounds.contains(center))
+            .map(|(bounds, water)| {
+                let bottom = center.y - collider.height / 2.0;
+                Submerged {
+                    water: *water,
+                    depth: ((bounds.max.y - bottom) / collider.height).clamp(0.0, 1.0),
+                }
+            });
+
+        match submerged {
+            Some(submerged) => {
+                commands.entity(entity).insert(submerged);
+            }
+            None if was_submerged => {
+                commands.entity(entity).remove::<Submerged>();
+            }
+            None => {}
+        }
+
+        if let Some(mut mode) = mode {
+            match (*mode, submerged.is_some()) {
+                (MovementMode::Normal, true) => *mode = MovementMode::Swimming,
+                (MovementMode::Swimming, false) => *mode = MovementMode::Normal,
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Pulls submerged bodies down with reduced gravity, lifts them by their
+/// buoyancy and slows them with the water's drag. Only [`DynamicBody`]s
+/// are moved.
+#[allow(clippy::type_complexity)]
+pub fn apply_swim_physics(
+    time: Res<Time>,
+    mut query: Query<
+        (&Gravity, &mut Velocity, &Submerged, Option<&MovementMode>),
+        With<DynamicBody>,
+    >,
+) {
+    let delta = time.delta_seconds();
+    for (gravity, mut velocity, submerged, mode) in query.iter_mut() {
+        if mode.is_some_and(|mode| !mode.has_gravity()) {
+            continue;
+        }
+        let water = &submerged.water;
+        velocity.0.y +=
+            (water.buoyancy * submerged.depth - gravity.0 * water.gravity_scale) * delta;
+        velocity.0 *= (-water.drag * delta).exp();
+    }
+}
+
+/// Drains [`Breath`] while fully under water and hurts entities that ran
+/// out of it
+pub fn update_breath(
+    time: Res<Time>,
+    mut query: Query<(&mut Breath, &mut Health, Option<&Submerged>)>,
+) {
+    for (mut breath, mut health, submerged) in query.iter_mut() {
+        let under_water = submerged.is_some_and(|submerged| submerged.depth >= 1.0);
+        let damage = breath.tick(time.delta_seconds(), under_water);
+        if damage > 0.0 {
+            health.take_damage(damage);
+        }
+    }
+}
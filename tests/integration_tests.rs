@@ -1,6 +1,31 @@
-use bevy::math::Vec2;
-use template_bevy::components::{AutoMove, DamageOnContact, Health, Obstacle, Speed};
-use template_bevy::resources::{GameSettings, GameTimer, ObstacleSpawnTimer, Score};
+use std::time::Duration;
+
+use bevy::prelude::*;
+use template_bevy::components::{
+    AutoMove, Bounce, BoxCollider, Breath, Climbable, Collectible, Collider, CollisionLayer,
+    CollisionLayers, DamageOnContact, DynamicBody, Enemy, Gravity, GroundSurface, Grounded, Health,
+    JumpConfig, KillZone, KinematicBody, LevelEntity, MaxSlope, MovementMode, Obstacle, Patrol,
+    Platform, Player, Pooled, PressurePlate, Pushable, Slope, Speed, SplinePath, Submerged,
+    SurfaceMaterial, Trigger, Velocity, WaterVolume,
+};
+use template_bevy::events::{Collision, Crushed, TriggerEntered, TriggerExited};
+use template_bevy::game::cli::CliArgs;
+use template_bevy::game::config::{GameConfig, ScoringConfig, ScreenEdge};
+use template_bevy::level::{LdtkProject, LevelData, TiledError, TiledMap};
+use template_bevy::resources::{
+    ActiveLevel, CameraBounds, CameraView, ConfigFile, Difficulty, EntityPool, GameRng,
+    GameSettings, GameTimer, LevelList, LevelResults, ObstacleSpawnTimer, Score, SpatialHash,
+    TileGrid, WaveState,
+};
+use template_bevy::systems::{
+    apply_auto_movement, apply_config, apply_crush_damage, apply_gravity, apply_swim_physics,
+    apply_velocity, bounce_obstacles, check_kill_zones, check_level_bounds,
+    check_platform_collisions, despawn_out_of_bounds, detect_collisions, detect_crushing,
+    follow_spline_paths, load_current_level, patrol_enemies, player_climb, player_jump,
+    player_movement, push_bodies, register_dynamic_bodies, spawn_obstacles, update_breath,
+    update_difficulty, update_pressure_plates, update_spatial_hash, update_submerged,
+    update_triggers,
+};
 
 #[test]
 fn test_health_creation() {
@@ -111,3 +136,1807 @@ fn test_obstacle_marker() {
     // Test that Obstacle is a valid marker component (can be constructed)
     let _obstacle = Obstacle;
 }
+
+fn load_level_file(path: &str) -> LevelData {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("assets")
+        .join(path);
+    let text = std::fs::read_to_string(&path).expect("level file should exist");
+    LevelData::from_ron(&text).expect("level file should parse")
+}
+
+#[test]
+fn test_level_completion_bonus() {
+    let mut levels = LevelList::default();
+    let level = load_level_file(levels.current_path().unwrap());
+    let mut timer = GameTimer::default();
+    timer.tick(20.0);
+    let health = Health::new(100.0);
+
+    let results = LevelResults::tally(&level, &timer, &health, &ScoringConfig::default());
+    assert_eq!(results.level_name, "Level 1");
+    assert!((results.time_remaining - 100.0).abs() < f32::EPSILON);
+    assert!(results.total_bonus() > 0);
+
+    let mut score = Score::default();
+    score.add(results.total_bonus());
+    assert_eq!(score.current, results.total_bonus());
+
+    assert!(levels.advance());
+    assert!(!levels.advance());
+}
+
+#[test]
+fn test_level_results_without_time_left() {
+    let mut level = load_level_file("levels/level_01.level.ron");
+    level.time_limit = 10.0;
+    let mut timer = GameTimer::default();
+    timer.tick(15.0);
+    let mut health = Health::new(100.0);
+    health.take_damage(100.0);
+
+    let results = LevelResults::tally(&level, &timer, &health, &ScoringConfig::default());
+    assert_eq!(results.time_bonus, 0);
+    assert_eq!(results.health_bonus, 0);
+    assert_eq!(results.total_bonus(), 0);
+}
+
+#[test]
+fn test_shipped_levels_are_valid() {
+    for path in LevelList::default().levels {
+        let level = load_level_file(&path);
+        if let Err(error) = level.validate() {
+            panic!("{path} is invalid: {error}");
+        }
+    }
+}
+
+#[test]
+fn test_shipped_config_matches_defaults() {
+    let config = GameConfig::load("game.config.ron", &[]).expect("config file should load");
+
+    let defaults = GameConfig::default();
+    assert_eq!(config.player.jump_velocity, defaults.player.jump_velocity);
+    assert_eq!(config.obstacles.spawn_edges, defaults.obstacles.spawn_edges);
+    assert_eq!(config.camera.offset, defaults.camera.offset);
+    assert_eq!(config.window.title, defaults.window.title);
+    assert_eq!(config.difficulty, defaults.difficulty);
+    assert_eq!(config.collision, defaults.collision);
+}
+
+#[test]
+fn test_command_line_overrides_config_file() {
+    let args = CliArgs::parse(["--set", "obstacles.spawn_interval=0.75"]).unwrap();
+    let config_file = ConfigFile::from_args(&args);
+    let config = GameConfig::load(&config_file.path, &config_file.overrides).unwrap();
+    assert_eq!(config.obstacles.spawn_interval, 0.75);
+
+    let args = CliArgs::parse(["--set", "obstacles.speed_min=900"]).unwrap();
+    let error = GameConfig::load("game.config.ron", &args.overrides).unwrap_err();
+    assert!(error.to_string().contains("obstacles.speed_min"));
+}
+
+#[test]
+fn test_hot_reload_keeps_config_that_overrides_make_invalid() {
+    let args = CliArgs::parse(["--set", "obstacles.speed_max=300"]).unwrap();
+    let mut config_file = ConfigFile::from_args(&args);
+    let mut assets = Assets::<GameConfig>::default();
+    config_file.handle = assets.add(GameConfig::default());
+
+    let mut app = App::new();
+    app.insert_resource(assets)
+        .insert_resource(config_file)
+        .init_resource::<GameConfig>()
+        .add_event::<AssetEvent<GameConfig>>()
+        .add_systems(Update, apply_config);
+
+    let edit_file = |app: &mut App, speed_min: f32| {
+        let handle = app.world().resource::<ConfigFile>().handle.clone();
+        let mut assets = app.world_mut().resource_mut::<Assets<GameConfig>>();
+        assets.get_mut(&handle).unwrap().obstacles.speed_min = speed_min;
+        app.world_mut()
+            .send_event(AssetEvent::Modified { id: handle.id() });
+        app.update();
+        app.world().resource::<GameConfig>().obstacles.clone()
+    };
+
+    let obstacles = edit_file(&mut app, 200.0);
+    assert_eq!((obstacles.speed_min, obstacles.speed_max), (200.0, 300.0));
+
+    // Valid on its own, but above the overridden maximum
+    let obstacles = edit_file(&mut app, 400.0);
+    assert_eq!((obstacles.speed_min, obstacles.speed_max), (200.0, 300.0));
+}
+
+#[test]
+fn test_level_01_matches_original_layout() {
+    let level = load_level_file("levels/level_01.level.ron");
+    assert_eq!(level.player_spawn, Vec2::new(0.0, 100.0));
+    assert_eq!(level.platforms.len(), 6);
+    assert_eq!(level.platforms[0].size, Vec2::new(800.0, 40.0));
+    assert!(level.one_way_platforms.is_empty());
+}
+
+fn load_tiled_fixture(name: &str) -> TiledMap {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/tiled")
+        .join(name);
+    let text = std::fs::read_to_string(&path).expect("fixture should exist");
+    TiledMap::from_json(&text).expect("fixture should parse")
+}
+
+#[test]
+fn test_tiled_map_import() {
+    let map = load_tiled_fixture("basic.tmj");
+    let level = map.to_level("basic").unwrap();
+    assert!(level.validate().is_ok());
+
+    assert_eq!(level.name, "Tiled Test Map");
+    assert_eq!(level.time_limit, 75.0);
+    assert_eq!(level.camera_bounds.unwrap().max, Vec2::new(160.0, 96.0));
+
+    // Ground row (including a flipped tile) merges into one strip,
+    // the floating run into another; the background layer is ignored
+    assert_eq!(level.platforms.len(), 2);
+    let ground = level
+        .platforms
+        .iter()
+        .find(|platform| platform.size.x == 160.0)
+        .expect("ground strip");
+    assert_eq!(ground.position, Vec2::new(80.0, 8.0));
+    assert_eq!(level.one_way_platforms.len(), 1);
+    assert_eq!(level.one_way_platforms[0].size, Vec2::new(48.0, 16.0));
+
+    assert_eq!(level.player_spawn, Vec2::new(24.0, 26.0));
+    assert_eq!(level.goal.unwrap().position, Vec2::new(144.0, 40.0));
+    assert_eq!(level.enemies.len(), 1);
+    assert_eq!(level.enemies[0].patrol_distance, 32.0);
+    assert_eq!(level.enemies[0].speed, 40.5);
+    assert_eq!(level.collectibles.len(), 1);
+    assert_eq!(level.collectibles[0].points, 25);
+    assert_eq!(level.collectibles[0].position, Vec2::new(48.0, 56.0));
+}
+
+#[test]
+fn test_tiled_map_rejects_unknown_object_class() {
+    let map = load_tiled_fixture("unknown_object.tmj");
+    let error = map.to_level("unknown_object").unwrap_err();
+    assert_eq!(
+        error,
+        TiledError::UnknownObjectClass {
+            layer: "entities".to_string(),
+            id: 2,
+            class: "chest".to_string(),
+        }
+    );
+}
+
+#[test]
+fn test_tiled_map_rejects_base64_layers() {
+    let map = load_tiled_fixture("base64_layer.tmj");
+    let error = map.to_level("base64_layer").unwrap_err();
+    assert_eq!(
+        error,
+        TiledError::UnsupportedEncoding {
+            layer: "ground".to_string(),
+            encoding: "base64".to_string(),
+        }
+    );
+}
+
+fn load_ldtk_fixture(name: &str) -> LdtkProject {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/ldtk")
+        .join(name);
+    let text = std::fs::read_to_string(&path).expect("fixture should exist");
+    LdtkProject::from_json(&text).expect("fixture should parse")
+}
+
+#[test]
+fn test_ldtk_project_import() {
+    let project = load_ldtk_fixture("world.ldtk");
+    let levels = project.to_levels("levels/world.ldtk").unwrap();
+    assert_eq!(levels.len(), 2);
+
+    let (identifier, first) = &levels[0];
+    assert_eq!(identifier, "Level_0");
+    assert!(first.validate().is_ok());
+    assert_eq!(first.time_limit, 60.0);
+    assert_eq!(first.player_spawn, Vec2::new(8.0, -24.0));
+    assert!(first.goal.is_none());
+
+    // IntGrid value 1 is solid, value 2 is identified as one-way
+    assert_eq!(first.platforms.len(), 1);
+    assert_eq!(first.platforms[0].position, Vec2::new(32.0, -40.0));
+    assert_eq!(first.platforms[0].size, Vec2::new(64.0, 16.0));
+    assert_eq!(first.one_way_platforms.len(), 1);
+    assert_eq!(first.one_way_platforms[0].position, Vec2::new(48.0, -24.0));
+
+    // Entity fields map by name regardless of case
+    assert_eq!(first.collectibles[0].points, 10);
+    assert_eq!(first.collectibles[0].position, Vec2::new(44.0, -12.0));
+}
+
+#[test]
+fn test_ldtk_levels_link_to_neighbours() {
+    let project = load_ldtk_fixture("world.ldtk");
+    let levels = project.to_levels("levels/world.ldtk").unwrap();
+    let (_, first) = &levels[0];
+    let (_, second) = &levels[1];
+
+    assert_eq!(
+        first.neighbours,
+        vec!["levels/world.ldtk#Level_1".to_string()]
+    );
+    assert_eq!(
+        second.neighbours,
+        vec!["levels/world.ldtk#Level_0".to_string()]
+    );
+
+    // Levels keep their world placement so they line up edge to edge
+    let first_bounds = first.world_bounds.unwrap();
+    let second_bounds = second.world_bounds.unwrap();
+    assert_eq!(first_bounds.max.x, second_bounds.min.x);
+    assert_eq!(second_bounds.min, Vec2::new(64.0, -48.0));
+
+    let goal = second.goal.as_ref().unwrap();
+    assert_eq!(goal.position, Vec2::new(120.0, -16.0));
+    assert_eq!(second.enemies[0].patrol_distance, 16.0);
+    assert_eq!(second.enemies[0].speed, 80.0);
+}
+
+/// An app running only `spawn_obstacles`, with time advanced manually
+fn obstacle_app(seed: u64, config: GameConfig) -> App {
+    let mut app = App::new();
+    app.insert_resource(GameRng::from_seed(seed))
+        .insert_resource(config)
+        .init_resource::<ObstacleSpawnTimer>()
+        .init_resource::<Difficulty>()
+        .init_resource::<WaveState>()
+        .init_resource::<CameraView>()
+        .init_resource::<EntityPool<Obstacle>>()
+        .init_resource::<Time>()
+        .add_systems(Update, spawn_obstacles);
+    app
+}
+
+/// Runs `spawn_obstacles` for `count` spawn intervals and returns the
+/// position, size and speed of every obstacle in spawn order
+fn obstacle_sequence(seed: u64, count: usize) -> Vec<(Vec3, Vec2, f32)> {
+    let mut app = obstacle_app(seed, GameConfig::default());
+
+    let interval = ObstacleSpawnTimer::default().timer.duration();
+    for _ in 0..count {
+        app.world_mut().resource_mut::<Time>().advance_by(interval);
+        app.update();
+    }
+
+    let mut query = app
+        .world_mut()
+        .query_filtered::<(Entity, &Transform, &Sprite, &AutoMove), With<Obstacle>>();
+    let mut obstacles: Vec<_> = query.iter(app.world()).collect();
+    obstacles.sort_by_key(|(entity, ..)| *entity);
+    obstacles
+        .into_iter()
+        .map(|(_, transform, sprite, auto_move)| {
+            (
+                transform.translation,
+                sprite.custom_size.unwrap(),
+                auto_move.speed,
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn test_same_seed_spawns_same_obstacles() {
+    let first = obstacle_sequence(1234, 5);
+    assert_eq!(first.len(), 5);
+    assert_eq!(first, obstacle_sequence(1234, 5));
+    assert_ne!(first, obstacle_sequence(4321, 5));
+}
+
+#[test]
+fn test_difficulty_director_shortens_spawn_interval() {
+    let mut app = App::new();
+    app.init_resource::<GameConfig>()
+        .init_resource::<Difficulty>()
+        .init_resource::<ObstacleSpawnTimer>()
+        .insert_resource(GameTimer {
+            elapsed: 180.0,
+            paused: false,
+        })
+        .add_systems(Update, update_difficulty);
+    app.update();
+
+    // The Normal preset doubles the spawn rate after three minutes
+    let difficulty = *app.world().resource::<Difficulty>();
+    assert_eq!(difficulty.spawn_rate, 2.0);
+    assert!(difficulty.speed > 1.0);
+    let timer = &app.world().resource::<ObstacleSpawnTimer>().timer;
+    assert_eq!(timer.duration().as_secs_f32(), 1.0);
+
+    let hard = GameConfig::from_ron("(difficulty: (preset: Hard))").unwrap();
+    assert!(hard.difficulty.sample(180.0).spawn_rate > difficulty.spawn_rate);
+}
+
+#[test]
+fn test_scripted_wave_timing() {
+    let config = GameConfig::from_ron(
+        r#"(waves: (
+            obstacle_types: {
+                "block": (size: (40.0, 40.0), speed: 200.0),
+            },
+            waves: [(
+                name: "gauntlet",
+                rest: 2.0,
+                entries: [
+                    (delay: 0.5, pattern: Single(kind: "block", y: 0.0)),
+                    (delay: 1.0, pattern: Wall(
+                        kind: "block", bottom: -200.0, top: 200.0, gap_y: 0.0, gap_height: 80.0,
+                    )),
+                    (pattern: Staircase(kind: "block", y: -200.0, steps: 3, rise: 40.0, spacing: 60.0)),
+                ],
+            )],
+        ))"#,
+    )
+    .unwrap();
+    assert!(config.validate().is_ok());
+
+    let mut app = obstacle_app(1, config);
+    let mut counts = Vec::new();
+    for _ in 0..17 {
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_millis(250));
+        app.update();
+        let count = app
+            .world_mut()
+            .query_filtered::<(), With<Obstacle>>()
+            .iter(app.world())
+            .count();
+        counts.push(count);
+    }
+
+    // Single at 0.5s, an 8 block wall and a 3 step staircase together at
+    // 1.5s, then a 2s rest before the wave repeats at 4.0s
+    assert_eq!(
+        counts,
+        vec![0, 1, 1, 1, 1, 12, 12, 12, 12, 12, 12, 12, 12, 12, 12, 13, 13]
+    );
+}
+
+#[test]
+fn test_wave_obstacles_grow_with_difficulty() {
+    let config = GameConfig::from_ron(
+        r#"(waves: (
+            obstacle_types: {
+                "ball": (size: (40.0, 40.0), speed: 200.0, shape: Circle),
+            },
+            waves: [(name: "balls", rest: 5.0, entries: [(delay: 0.5, pattern: Single(kind: "ball", y: 0.0))])],
+        ))"#,
+    )
+    .unwrap();
+    let mut app = obstacle_app(1, config);
+    app.insert_resource(Difficulty {
+        size: 1.5,
+        ..default()
+    });
+    app.world_mut()
+        .resource_mut::<Time>()
+        .advance_by(Duration::from_secs(1));
+    app.update();
+
+    let (collider, shape, sprite) = app
+        .world_mut()
+        .query_filtered::<(&BoxCollider, &Collider, &Sprite), With<Obstacle>>()
+        .single(app.world());
+    assert_eq!((collider.width, collider.height), (60.0, 60.0));
+    assert_eq!(*shape, Collider::circle(30.0));
+    assert_eq!(sprite.custom_size, Some(Vec2::splat(60.0)));
+}
+
+#[test]
+fn test_picking_waves_does_not_shift_obstacle_rolls() {
+    /// Plays the first entry of `waves` and returns the random obstacle it spawns
+    fn first_random_obstacle(seed: u64, waves: &str) -> (Vec3, Vec2, f32) {
+        let config = GameConfig::from_ron(&format!("(waves: ({waves}))")).unwrap();
+        let mut app = obstacle_app(seed, config);
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs(1));
+        app.update();
+
+        let (transform, sprite, auto_move) = app
+            .world_mut()
+            .query_filtered::<(&Transform, &Sprite, &AutoMove), With<Obstacle>>()
+            .single(app.world());
+        (
+            transform.translation,
+            sprite.custom_size.unwrap(),
+            auto_move.speed,
+        )
+    }
+
+    let wave = r#"(name: "scatter", rest: 5.0, entries: [(delay: 0.5, pattern: Random)])"#;
+    for seed in 0..8 {
+        let in_order = first_random_obstacle(seed, &format!("waves: [{wave}]"));
+        let weighted = first_random_obstacle(
+            seed,
+            &format!("order: WeightedRandom, waves: [{wave}, {wave}]"),
+        );
+        assert_eq!(in_order, weighted, "seed {seed}");
+    }
+}
+
+#[test]
+fn test_finished_spline_path_keeps_moving() {
+    let mut app = App::new();
+    app.init_resource::<Time>()
+        .add_systems(Update, (follow_spline_paths, apply_auto_movement).chain());
+    let obstacle = app
+        .world_mut()
+        .spawn((
+            SplinePath::new(vec![Vec2::ZERO, Vec2::new(-50.0, 50.0)], 100.0, false),
+            Transform::default(),
+        ))
+        .id();
+
+    for _ in 0..10 {
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_millis(100));
+        app.update();
+    }
+
+    // The path ends after about 0.7s, then the obstacle carries on up and to
+    // the left instead of stopping at its last point
+    let entity = app.world().entity(obstacle);
+    assert!(!entity.contains::<SplinePath>());
+    let auto_move = entity.get::<AutoMove>().unwrap();
+    assert!(auto_move
+        .direction
+        .abs_diff_eq(Vec2::new(-1.0, 1.0).normalize(), 1e-6));
+    assert_eq!(auto_move.speed, 100.0);
+    let position = entity.get::<Transform>().unwrap().translation.truncate();
+    assert!(position.x < -60.0 && position.y > 60.0);
+}
+
+#[test]
+fn test_loading_a_level_pools_obstacles() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+        .init_asset::<LevelData>()
+        .init_resource::<LevelList>()
+        .init_resource::<GameTimer>()
+        .init_resource::<WaveState>()
+        .init_resource::<ActiveLevel>()
+        .init_resource::<ClearColor>()
+        .init_resource::<CameraBounds>()
+        .init_resource::<TileGrid>()
+        .init_resource::<EntityPool<Obstacle>>()
+        .add_systems(Update, load_current_level);
+    let obstacle = app
+        .world_mut()
+        .spawn((Obstacle, SpriteBundle::default()))
+        .id();
+    let platform = app
+        .world_mut()
+        .spawn((Platform, LevelEntity(Handle::default().id())))
+        .id();
+    app.update();
+
+    // The obstacle is kept for reuse, the old level's platform is gone
+    assert!(app.world().get::<Pooled>(obstacle).is_some());
+    assert_eq!(
+        app.world().resource::<EntityPool<Obstacle>>().available(),
+        1
+    );
+    assert!(app.world().get_entity(platform).is_none());
+}
+
+#[test]
+fn test_bouncing_obstacle_rebounds_off_platform() {
+    let mut app = App::new();
+    app.init_resource::<Time>()
+        .init_resource::<SpatialHash>()
+        .add_systems(Update, (update_spatial_hash, bounce_obstacles).chain());
+    app.world_mut().spawn((
+        Platform,
+        BoxCollider::new(400.0, 20.0),
+        Transform::from_xyz(0.0, -10.0, 0.0),
+    ));
+    let obstacle = app
+        .world_mut()
+        .spawn((
+            Bounce::new(1000.0, 0.5),
+            BoxCollider::new(20.0, 20.0),
+            Transform::from_xyz(0.0, 60.0, 0.0),
+        ))
+        .id();
+
+    let mut lowest = f32::MAX;
+    let mut bounced_speed = 0.0_f32;
+    for _ in 0..30 {
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_millis(20));
+        app.update();
+        let y = app
+            .world()
+            .get::<Transform>(obstacle)
+            .unwrap()
+            .translation
+            .y;
+        lowest = lowest.min(y);
+        bounced_speed = bounced_speed.max(app.world().get::<Bounce>(obstacle).unwrap().velocity_y);
+    }
+
+    // Never sinks into the platform and comes back up with half its speed
+    assert!(lowest >= 10.0);
+    assert!(bounced_speed > 100.0 && bounced_speed < 200.0);
+}
+
+#[test]
+fn test_obstacles_spawn_relative_to_camera_view() {
+    let mut config = GameConfig::default();
+    config.obstacles.spawn_edges = vec![ScreenEdge::Top];
+    let mut app = obstacle_app(7, config);
+    let view = Rect::from_center_size(Vec2::new(5000.0, 300.0), Vec2::new(1280.0, 720.0));
+    app.insert_resource(CameraView(view));
+
+    let interval = ObstacleSpawnTimer::default().timer.duration();
+    app.world_mut().resource_mut::<Time>().advance_by(interval);
+    app.update();
+
+    let (transform, auto_move) = app
+        .world_mut()
+        .query_filtered::<(&Transform, &AutoMove), With<Obstacle>>()
+        .single(app.world());
+    assert_eq!(transform.translation.y, view.max.y + 60.0);
+    assert!((view.min.x..view.max.x).contains(&transform.translation.x));
+    assert_eq!(auto_move.direction, Vec2::NEG_Y);
+}
+
+#[test]
+fn test_obstacle_pool_reuses_entities() {
+    let mut app = obstacle_app(3, GameConfig::default());
+    app.init_resource::<ActiveLevel>()
+        .init_resource::<Assets<LevelData>>()
+        .add_systems(Update, despawn_out_of_bounds.after(spawn_obstacles));
+    let interval = ObstacleSpawnTimer::default().timer.duration();
+    app.world_mut().resource_mut::<Time>().advance_by(interval);
+    app.update();
+
+    let obstacle = app
+        .world_mut()
+        .query_filtered::<Entity, With<Obstacle>>()
+        .single(app.world());
+
+    // Move the view far to the right, leaving the obstacle behind
+    let view = Rect::from_center_size(Vec2::new(10_000.0, 0.0), Vec2::new(1280.0, 720.0));
+    app.insert_resource(CameraView(view));
+    app.update();
+
+    let pooled = app.world().entity(obstacle);
+    assert!(pooled.contains::<Pooled>());
+    assert!(!pooled.contains::<Obstacle>());
+    assert!(!pooled.contains::<AutoMove>());
+    assert_eq!(pooled.get::<Visibility>(), Some(&Visibility::Hidden));
+    assert_eq!(
+        app.world().resource::<EntityPool<Obstacle>>().available(),
+        1
+    );
+
+    // The next spawn reuses the same entity
+    app.world_mut().resource_mut::<Time>().advance_by(interval);
+    app.update();
+    let reused = app.world().entity(obstacle);
+    assert!(reused.contains::<Obstacle>());
+    assert!(!reused.contains::<Pooled>());
+    assert_eq!(reused.get::<Visibility>(), Some(&Visibility::Inherited));
+    assert_eq!(
+        app.world().resource::<EntityPool<Obstacle>>().available(),
+        0
+    );
+}
+
+#[test]
+fn test_collisions_respect_layers() {
+    let mut app = App::new();
+    app.init_resource::<SpatialHash>()
+        .add_event::<Collision>()
+        .add_systems(Update, (update_spatial_hash, detect_collisions).chain());
+
+    let mut collision = GameConfig::default().collision;
+    collision.enemy.push(CollisionLayer::Enemy);
+    let mut spawn = |layer: CollisionLayer, x: f32| {
+        app.world_mut()
+            .spawn((
+                BoxCollider::new(40.0, 40.0),
+                collision.layers(layer),
+                Transform::from_xyz(x, 0.0, 0.0),
+            ))
+            .id()
+    };
+    // Each collider overlaps its neighbours
+    let pickup = spawn(CollisionLayer::Pickup, -30.0);
+    let player = spawn(CollisionLayer::Player, 0.0);
+    let enemy = spawn(CollisionLayer::Enemy, 30.0);
+    let other_enemy = spawn(CollisionLayer::Enemy, 60.0);
+    spawn(CollisionLayer::Obstacle, 90.0);
+    app.update();
+
+    let mut pairs: Vec<[Entity; 2]> = app
+        .world_mut()
+        .resource_mut::<Events<Collision>>()
+        .drain()
+        .map(|collision| {
+            let mut pair = [collision.a, collision.b];
+            pair.sort();
+            pair
+        })
+        .collect();
+    pairs.sort();
+
+    // Enemies ignore obstacles, and each pair is reported once
+    let mut expected = vec![[pickup, player], [player, enemy], [enemy, other_enemy]];
+    expected.iter_mut().for_each(|pair| pair.sort());
+    expected.sort();
+    assert_eq!(pairs, expected);
+}
+
+/// Drops a player with an optional shape so it hangs 12 pixels over the
+/// right edge of a platform, and returns where it ends up
+fn settle_over_ledge(shape: Option<Collider>) -> (Vec3, bool) {
+    let mut app = App::new();
+    app.init_resource::<Time>()
+        .init_resource::<SpatialHash>()
+        .add_systems(
+            Update,
+            (
+                apply_gravity,
+                apply_velocity,
+                update_spatial_hash,
+                check_platform_collisions,
+            )
+                .chain(),
+        );
+    app.world_mut().spawn((
+        Platform,
+        BoxCollider::new(200.0, 20.0),
+        Transform::from_xyz(0.0, -10.0, 0.0),
+    ));
+    let mut player = app.world_mut().spawn((
+        Player,
+        Velocity::default(),
+        Gravity(980.0),
+        DynamicBody,
+        Grounded(false),
+        BoxCollider::new(40.0, 50.0),
+        Transform::from_xyz(112.0, 27.0, 0.0),
+    ));
+    if let Some(shape) = shape {
+        player.insert(shape);
+    }
+    let player = player.id();
+
+    for _ in 0..60 {
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(1.0 / 60.0));
+        app.update();
+    }
+    let world = app.world();
+    (
+        world.get::<Transform>(player).unwrap().translation,
+        world.get::<Grounded>(player).unwrap().0,
+    )
+}
+
+#[test]
+fn test_capsule_player_slides_off_ledge() {
+    // A box stands on the very edge of the platform
+    let (position, grounded) = settle_over_ledge(None);
+    assert!(grounded);
+    assert_eq!(position, Vec3::new(112.0, 25.0, 0.0));
+
+    // A capsule rolls off the corner and falls
+    let (position, grounded) = settle_over_ledge(Some(Collider::capsule(20.0, 5.0)));
+    assert!(!grounded);
+    assert!(position.x > 120.0, "{position}");
+    assert!(position.y < 0.0, "{position}");
+}
+
+/// Walks a player right from `start_x` across flat ground and a ramp
+/// `width` wide, returning its position after every frame and whether it
+/// stayed grounded throughout
+fn walk_over_ramp(slope: Slope, width: f32, start_x: f32) -> (Vec<Vec3>, bool) {
+    let mut app = App::new();
+    app.init_resource::<Time>()
+        .init_resource::<SpatialHash>()
+        .add_systems(
+            Update,
+            (
+                |mut query: Query<&mut Velocity, With<Player>>| {
+                    for mut velocity in &mut query {
+                        velocity.0.x = 100.0;
+                    }
+                },
+                apply_gravity,
+                apply_velocity,
+                update_spatial_hash,
+                check_platform_collisions,
+            )
+                .chain(),
+        );
+    app.world_mut().spawn((
+        Platform,
+        BoxCollider::new(600.0, 20.0),
+        Transform::from_xyz(0.0, -10.0, 0.0),
+    ));
+    app.world_mut().spawn((
+        Platform,
+        slope,
+        BoxCollider::new(width, 100.0),
+        Transform::from_xyz(width / 2.0, 50.0, 0.0),
+    ));
+    let player = app
+        .world_mut()
+        .spawn((
+            Player,
+            Velocity::default(),
+            Gravity(980.0),
+            DynamicBody,
+            Grounded(true),
+            MaxSlope::default(),
+            BoxCollider::new(40.0, 50.0),
+            Transform::from_xyz(start_x, 25.0 + slope.height_at(start_x, width), 0.0),
+        ))
+        .id();
+
+    let mut path = Vec::new();
+    let mut always_grounded = true;
+    for _ in 0..120 {
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(1.0 / 60.0));
+        app.update();
+        let world = app.world();
+        path.push(world.get::<Transform>(player).unwrap().translation);
+        always_grounded &= world.get::<Grounded>(player).unwrap().0;
+    }
+    (path, always_grounded)
+}
+
+#[test]
+fn test_player_walks_up_gentle_ramp_and_slides_off_steep_one() {
+    // About 27°: the feet follow the surface without leaving the ground
+    let (path, always_grounded) = walk_over_ramp(Slope::new(0.0, 100.0), 200.0, -50.0);
+    assert!(always_grounded);
+    let end = *path.last().unwrap();
+    assert!((end.x - 150.0).abs() < 1.0, "{end}");
+    assert!((end.y - (25.0 + 75.0)).abs() < 1.0, "{end}");
+
+    // Walking down it snaps to the surface instead of launching off
+    let (path, always_grounded) = walk_over_ramp(Slope::new(100.0, 0.0), 200.0, 10.0);
+    assert!(always_grounded);
+    assert_eq!(path.last().unwrap().y, 25.0);
+
+    // About 68°: the player is pushed back and never climbs it
+    let (path, _) = walk_over_ramp(Slope::new(0.0, 100.0), 40.0, -50.0);
+    let highest = path.iter().map(|p| p.y).fold(f32::MIN, f32::max);
+    assert!(highest < 25.0 + 20.0, "climbed to {highest}");
+    assert!(path.last().unwrap().x < 20.0, "{}", path.last().unwrap());
+}
+
+/// Runs a player with no input held over a platform made of `material`
+/// for `frames` frames, starting at `position` with `velocity`
+fn move_on_surface(
+    material: SurfaceMaterial,
+    position: Vec2,
+    velocity: Vec2,
+    frames: usize,
+) -> (Vec3, Vec2, bool) {
+    let mut app = App::new();
+    app.init_resource::<Time>()
+        .init_resource::<ButtonInput<KeyCode>>()
+        .init_resource::<SpatialHash>()
+        .add_systems(
+            Update,
+            (
+                player_movement,
+                apply_gravity,
+                apply_velocity,
+                update_spatial_hash,
+                check_platform_collisions,
+            )
+                .chain(),
+        );
+    app.world_mut().spawn((
+        Platform,
+        material,
+        BoxCollider::new(2000.0, 20.0),
+        Transform::from_xyz(0.0, -10.0, 0.0),
+    ));
+    let player = app
+        .world_mut()
+        .spawn((
+            Player,
+            Speed(200.0),
+            Velocity(velocity),
+            Gravity(980.0),
+            DynamicBody,
+            Grounded(velocity.y == 0.0),
+            GroundSurface(material),
+            BoxCollider::new(40.0, 50.0),
+            Transform::from_translation(position.extend(0.0)),
+        ))
+        .id();
+
+    for _ in 0..frames {
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(1.0 / 60.0));
+        app.update();
+    }
+    let world = app.world();
+    (
+        world.get::<Transform>(player).unwrap().translation,
+        world.get::<Velocity>(player).unwrap().0,
+        world.get::<Grounded>(player).unwrap().0,
+    )
+}
+
+#[test]
+fn test_surface_materials() {
+    let standing = Vec2::new(0.0, 25.0);
+    let running = Vec2::new(200.0, 0.0);
+
+    // Letting go stops the player at once on normal ground but not on ice
+    let (_, velocity, _) = move_on_surface(SurfaceMaterial::default(), standing, running, 5);
+    assert_eq!(velocity.x, 0.0);
+    let ice = SurfaceMaterial {
+        friction: 0.05,
+        ..default()
+    };
+    let (_, velocity, grounded) = move_on_surface(ice, standing, running, 5);
+    assert!(grounded);
+    assert!(velocity.x > 100.0, "{velocity}");
+
+    // A conveyor carries a standing player along
+    let conveyor = SurfaceMaterial {
+        conveyor: -60.0,
+        ..default()
+    };
+    let (position, _, grounded) = move_on_surface(conveyor, standing, Vec2::ZERO, 60);
+    assert!(grounded);
+    assert!((position.x + 60.0).abs() < 1.5, "{position}");
+
+    // A bounce pad sends a falling player back up
+    let pad = SurfaceMaterial {
+        bounciness: 1.0,
+        ..default()
+    };
+    let (position, velocity, grounded) =
+        move_on_surface(pad, Vec2::new(0.0, 30.0), Vec2::new(0.0, -300.0), 2);
+    assert!(!grounded);
+    assert!(velocity.y > 250.0, "{velocity}");
+    assert!(position.y >= 25.0, "{position}");
+}
+
+#[test]
+fn test_player_climbs_ladder() {
+    let mut app = App::new();
+    app.init_resource::<Time>()
+        .init_resource::<ButtonInput<KeyCode>>()
+        .init_resource::<SpatialHash>()
+        .add_systems(
+            Update,
+            (
+                update_spatial_hash,
+                player_climb,
+                apply_gravity,
+                apply_velocity,
+            )
+                .chain(),
+        );
+    app.world_mut().spawn((
+        Climbable,
+        BoxCollider::new(30.0, 200.0),
+        Transform::from_xyz(0.0, 100.0, 0.0),
+    ));
+    let player = app
+        .world_mut()
+        .spawn((
+            Player,
+            Speed(120.0),
+            JumpConfig::default(),
+            Velocity::default(),
+            Gravity(980.0),
+            DynamicBody,
+            Grounded(false),
+            MovementMode::default(),
+            BoxCollider::new(40.0, 50.0),
+            Transform::from_xyz(0.0, 50.0, 0.0),
+        ))
+        .id();
+    let run = |app: &mut App, frames: usize| {
+        for _ in 0..frames {
+            app.world_mut()
+                .resource_mut::<Time>()
+                .advance_by(Duration::from_secs_f32(1.0 / 60.0));
+            app.update();
+            app.world_mut()
+                .resource_mut::<ButtonInput<KeyCode>>()
+                .clear();
+        }
+    };
+
+    // Holding up grabs the ladder and climbs at the player's speed
+    app.world_mut()
+        .resource_mut::<ButtonInput<KeyCode>>()
+        .press(KeyCode::KeyW);
+    run(&mut app, 30);
+    let world = app.world();
+    assert_eq!(
+        world.get::<MovementMode>(player),
+        Some(&MovementMode::Climbing)
+    );
+    let y = world.get::<Transform>(player).unwrap().translation.y;
+    assert!((y - 110.0).abs() < 2.5, "{y}");
+
+    // Letting go hangs on without falling
+    app.world_mut()
+        .resource_mut::<ButtonInput<KeyCode>>()
+        .release(KeyCode::KeyW);
+    run(&mut app, 30);
+    let after = app.world().get::<Transform>(player).unwrap().translation.y;
+    assert_eq!(after, y);
+
+    // Jumping lets go
+    app.world_mut()
+        .resource_mut::<ButtonInput<KeyCode>>()
+        .press(KeyCode::Space);
+    run(&mut app, 1);
+    let world = app.world();
+    assert_eq!(
+        world.get::<MovementMode>(player),
+        Some(&MovementMode::Normal)
+    );
+    assert!(world.get::<Velocity>(player).unwrap().0.y > 0.0);
+}
+
+#[test]
+fn test_swimming_in_water() {
+    let mut app = App::new();
+    app.init_resource::<Time>()
+        .init_resource::<ButtonInput<KeyCode>>()
+        .init_resource::<SpatialHash>()
+        .add_systems(
+            Update,
+            (
+                update_spatial_hash,
+                update_submerged,
+                player_jump,
+                apply_gravity,
+                apply_swim_physics,
+                apply_velocity,
+                patrol_enemies,
+                update_breath,
+            )
+                .chain(),
+        );
+    app.world_mut().spawn((
+        WaterVolume::default(),
+        BoxCollider::new(1000.0, 400.0),
+        Transform::from_xyz(0.0, -200.0, 0.0),
+    ));
+    let spawn_player = |app: &mut App, x: f32, y: f32| {
+        app.world_mut()
+            .spawn((
+                Player,
+                JumpConfig::default(),
+                Velocity::default(),
+                Gravity(980.0),
+                DynamicBody,
+                Grounded(false),
+                MovementMode::default(),
+                Health::new(100.0),
+                Breath::new(0.5, 20.0),
+                BoxCollider::new(40.0, 50.0),
+                Transform::from_xyz(x, y, 0.0),
+            ))
+            .id()
+    };
+    let swimmer = spawn_player(&mut app, 0.0, -200.0);
+    let faller = spawn_player(&mut app, 2000.0, -200.0);
+    let wader = app
+        .world_mut()
+        .spawn((
+            Patrol::new(-400.0, 200.0, 100.0),
+            BoxCollider::new(40.0, 40.0),
+            Transform::from_xyz(-400.0, -200.0, 0.0),
+        ))
+        .id();
+    let run = |app: &mut App, frames: usize| {
+        for _ in 0..frames {
+            app.world_mut()
+                .resource_mut::<Time>()
+                .advance_by(Duration::from_secs_f32(1.0 / 60.0));
+            app.update();
+            app.world_mut()
+                .resource_mut::<ButtonInput<KeyCode>>()
+                .clear();
+        }
+    };
+
+    run(&mut app, 60);
+    let world = app.world();
+    assert_eq!(
+        world.get::<MovementMode>(swimmer),
+        Some(&MovementMode::Swimming)
+    );
+    assert_eq!(
+        world.get::<MovementMode>(faller),
+        Some(&MovementMode::Normal)
+    );
+    // Reduced gravity, buoyancy and drag make the swimmer sink slowly
+    let sunk = -200.0 - world.get::<Transform>(swimmer).unwrap().translation.y;
+    let fallen = -200.0 - world.get::<Transform>(faller).unwrap().translation.y;
+    assert!(
+        sunk > 0.0 && sunk < fallen / 10.0,
+        "sank {sunk}, fell {fallen}"
+    );
+    // The patrol wades at half speed
+    let waded = world.get::<Transform>(wader).unwrap().translation.x + 400.0;
+    assert!((waded - 50.0).abs() < 1.0, "{waded}");
+    assert!(world.get::<Submerged>(wader).is_some());
+    // Out of breath after half a second under water
+    let health = world.get::<Health>(swimmer).unwrap().current;
+    assert!((health - 90.0).abs() < 0.5, "{health}");
+
+    // Each stroke pushes the swimmer up, even without ground under it
+    for _ in 0..2 {
+        app.world_mut()
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(KeyCode::Space);
+        run(&mut app, 1);
+        let velocity = app.world().get::<Velocity>(swimmer).unwrap().0;
+        assert!(velocity.y > 200.0, "{velocity}");
+        app.world_mut()
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .release(KeyCode::Space);
+        run(&mut app, 10);
+    }
+}
+
+#[test]
+fn test_trigger_enter_and_exit_events() {
+    let mut app = App::new();
+    app.init_resource::<SpatialHash>()
+        .add_event::<Collision>()
+        .add_event::<TriggerEntered>()
+        .add_event::<TriggerExited>()
+        .add_systems(
+            Update,
+            (update_spatial_hash, detect_collisions, update_triggers).chain(),
+        );
+
+    let layers = GameConfig::default().collision;
+    let trigger = app
+        .world_mut()
+        .spawn((
+            Trigger::default(),
+            BoxCollider::new(100.0, 100.0),
+            CollisionLayers::new(&[], &[CollisionLayer::Player]),
+            Transform::default(),
+        ))
+        .id();
+    let player = app
+        .world_mut()
+        .spawn((
+            BoxCollider::new(40.0, 40.0),
+            layers.layers(CollisionLayer::Player),
+            Transform::from_xyz(-200.0, 0.0, 0.0),
+        ))
+        .id();
+    // Enemies are not in the trigger's filter
+    app.world_mut().spawn((
+        BoxCollider::new(40.0, 40.0),
+        layers.layers(CollisionLayer::Enemy),
+        Transform::default(),
+    ));
+
+    let step = |app: &mut App, x: Option<f32>| {
+        if let Some(x) = x {
+            app.world_mut()
+                .get_mut::<Transform>(player)
+                .unwrap()
+                .translation
+                .x = x;
+        }
+        app.update();
+        let entered: Vec<TriggerEntered> = app
+            .world_mut()
+            .resource_mut::<Events<TriggerEntered>>()
+            .drain()
+            .collect();
+        let exited: Vec<TriggerExited> = app
+            .world_mut()
+            .resource_mut::<Events<TriggerExited>>()
+            .drain()
+            .collect();
+        (entered, exited)
+    };
+    let event = (trigger, player);
+
+    assert_eq!(step(&mut app, None), (vec![], vec![]));
+    let (entered, exited) = step(&mut app, Some(0.0));
+    assert_eq!(
+        (entered.len(), exited.len()),
+        (1, 0),
+        "only the player enters"
+    );
+    assert_eq!((entered[0].trigger, entered[0].entity), event);
+    // Moving around inside sends nothing
+    assert_eq!(step(&mut app, Some(20.0)), (vec![], vec![]));
+    let (entered, exited) = step(&mut app, Some(200.0));
+    assert!(entered.is_empty());
+    assert_eq!((exited[0].trigger, exited[0].entity), event);
+
+    // Despawning inside counts as leaving
+    step(&mut app, Some(0.0));
+    app.world_mut().despawn(player);
+    let (_, exited) = step(&mut app, None);
+    assert_eq!((exited[0].trigger, exited[0].entity), event);
+
+    // Triggers are sensors, they never collide
+    assert!(app
+        .world_mut()
+        .resource_mut::<Events<Collision>>()
+        .drain()
+        .all(|collision| collision.a != trigger && collision.b != trigger));
+}
+
+#[test]
+fn test_kill_zones_and_level_bounds() {
+    let level = LevelData::from_ron(
+        r#"(
+            name: "Pit",
+            player_spawn: (0.0, 0.0),
+            bounds: (min: (-1000.0, -500.0), max: (1000.0, 500.0)),
+        )"#,
+    )
+    .unwrap();
+    let mut levels = Assets::<LevelData>::default();
+    let active_level = ActiveLevel {
+        handle: levels.add(level),
+        neighbours: Vec::new(),
+    };
+
+    let mut app = App::new();
+    app.insert_resource(levels)
+        .insert_resource(active_level)
+        .init_resource::<GameConfig>()
+        .init_resource::<CameraView>()
+        .init_resource::<EntityPool<Obstacle>>()
+        .init_resource::<SpatialHash>()
+        .add_event::<TriggerEntered>()
+        .add_event::<TriggerExited>()
+        .add_systems(
+            Update,
+            (
+                update_spatial_hash,
+                update_triggers,
+                check_kill_zones,
+                check_level_bounds,
+                despawn_out_of_bounds,
+            )
+                .chain(),
+        );
+
+    let layers = GameConfig::default().collision;
+    app.world_mut().spawn((
+        KillZone { damage: Some(30.0) },
+        Trigger::default(),
+        BoxCollider::new(100.0, 100.0),
+        CollisionLayers::new(&[], &[CollisionLayer::Player]),
+        Transform::from_xyz(500.0, 0.0, 0.0),
+    ));
+    let player = app
+        .world_mut()
+        .spawn((
+            Player,
+            Velocity(Vec2::new(0.0, -300.0)),
+            Health::new(100.0),
+            MovementMode::Climbing,
+            BoxCollider::new(40.0, 40.0),
+            layers.layers(CollisionLayer::Player),
+            Transform::from_xyz(500.0, 0.0, 0.0),
+        ))
+        .id();
+    let player_state = |app: &App| {
+        let world = app.world();
+        (
+            world
+                .get::<Transform>(player)
+                .unwrap()
+                .translation
+                .truncate(),
+            world.get::<Velocity>(player).unwrap().0,
+            world.get::<Health>(player).unwrap().current,
+            *world.get::<MovementMode>(player).unwrap(),
+        )
+    };
+
+    // The kill zone hurts and respawns the player
+    app.update();
+    assert_eq!(
+        player_state(&app),
+        (Vec2::ZERO, Vec2::ZERO, 70.0, MovementMode::Normal)
+    );
+
+    // Falling out of the level kills by default, and the player comes back
+    // with full health
+    app.world_mut()
+        .get_mut::<Transform>(player)
+        .unwrap()
+        .translation
+        .y = -600.0;
+    app.update();
+    assert_eq!(
+        player_state(&app),
+        (Vec2::ZERO, Vec2::ZERO, 100.0, MovementMode::Normal)
+    );
+
+    app.world_mut()
+        .resource_mut::<GameConfig>()
+        .player
+        .out_of_bounds_damage = Some(25.0);
+    app.world_mut()
+        .get_mut::<Transform>(player)
+        .unwrap()
+        .translation
+        .x = 1200.0;
+    app.update();
+    assert_eq!(player_state(&app).2, 75.0);
+
+    // Anything else that leaves the bounds is removed, obstacles into the pool
+    let enemy = app
+        .world_mut()
+        .spawn((
+            Patrol::new(0.0, 100.0, 80.0),
+            Transform::from_xyz(0.0, -700.0, 0.0),
+        ))
+        .id();
+    let obstacle = app
+        .world_mut()
+        .spawn((
+            Obstacle,
+            AutoMove::left(100.0),
+            Transform::from_xyz(0.0, -700.0, 0.0),
+        ))
+        .id();
+    let ground = app
+        .world_mut()
+        .spawn((Platform, Transform::from_xyz(0.0, -700.0, 0.0)))
+        .id();
+    app.update();
+    assert!(app.world().get_entity(enemy).is_none());
+    assert!(app.world().entity(obstacle).contains::<Pooled>());
+    assert!(app.world().get_entity(ground).is_some());
+}
+
+/// App that moves solids and crushes whatever they squeeze, respawning the
+/// player at (500, 25), with a player resting on a floor at y = 0
+fn crush_app(config: GameConfig) -> (App, Entity, Entity) {
+    let level = LevelData::from_ron(r#"(name: "Press", player_spawn: (500.0, 25.0))"#).unwrap();
+    let mut levels = Assets::<LevelData>::default();
+    let active_level = ActiveLevel {
+        handle: levels.add(level),
+        neighbours: Vec::new(),
+    };
+
+    let mut app = App::new();
+    app.insert_resource(levels)
+        .insert_resource(active_level)
+        .insert_resource(config)
+        .init_resource::<Time>()
+        .init_resource::<SpatialHash>()
+        .init_resource::<EntityPool<Obstacle>>()
+        .add_event::<Crushed>()
+        .add_systems(
+            Update,
+            (
+                apply_velocity,
+                update_spatial_hash,
+                check_platform_collisions,
+                detect_crushing,
+                apply_crush_damage,
+            )
+                .chain(),
+        );
+
+    let floor = app
+        .world_mut()
+        .spawn((
+            Platform,
+            BoxCollider::new(400.0, 40.0),
+            Transform::from_xyz(0.0, -20.0, 0.0),
+        ))
+        .id();
+    let player = app
+        .world_mut()
+        .spawn((
+            Player,
+            Velocity::default(),
+            DynamicBody,
+            Grounded(true),
+            Health::new(100.0),
+            BoxCollider::new(40.0, 50.0),
+            Transform::from_xyz(0.0, 25.0, 0.0),
+        ))
+        .id();
+    (app, floor, player)
+}
+
+/// Runs frames until something is crushed, returning the frame and events
+fn run_until_crushed(app: &mut App) -> (usize, Vec<Crushed>) {
+    for frame in 0..120 {
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(1.0 / 60.0));
+        app.update();
+        let crushed: Vec<Crushed> = app
+            .world_mut()
+            .resource_mut::<Events<Crushed>>()
+            .drain()
+            .collect();
+        if !crushed.is_empty() {
+            return (frame, crushed);
+        }
+    }
+    panic!("nothing was crushed");
+}
+
+#[test]
+fn test_crushed_by_descending_ceiling() {
+    let (mut app, floor, player) = crush_app(GameConfig::default());
+    // 80 pixels of headroom, closing at 100 pixels per second
+    let ceiling = app
+        .world_mut()
+        .spawn((
+            Platform,
+            Velocity(Vec2::new(0.0, -100.0)),
+            BoxCollider::new(400.0, 40.0),
+            Transform::from_xyz(0.0, 100.0, 0.0),
+        ))
+        .id();
+
+    let (frame, crushed) = run_until_crushed(&mut app);
+    // Crushed once less than 42 pixels are left for the 50 pixel tall player
+    assert!((20..26).contains(&frame), "crushed on frame {frame}");
+    assert_eq!(
+        crushed,
+        vec![Crushed {
+            entity: player,
+            solids: [floor, ceiling],
+        }]
+    );
+
+    // Lethal by default: the player respawns with a fresh life
+    let world = app.world();
+    assert_eq!(
+        world.get::<Transform>(player).unwrap().translation,
+        Vec3::new(500.0, 25.0, 0.0)
+    );
+    assert_eq!(world.get::<Health>(player).unwrap().current, 100.0);
+}
+
+#[test]
+fn test_crushed_against_wall() {
+    let mut config = GameConfig::default();
+    config.player.crush_damage = Some(30.0);
+    let (mut app, _, player) = crush_app(config);
+    // Static walls alone never crush, even overlapping the player
+    let wall = app
+        .world_mut()
+        .spawn((
+            Platform,
+            BoxCollider::new(40.0, 200.0),
+            Transform::from_xyz(-40.0, 100.0, 0.0),
+        ))
+        .id();
+    let right_wall = app
+        .world_mut()
+        .spawn((
+            Platform,
+            BoxCollider::new(40.0, 200.0),
+            Transform::from_xyz(30.0, 100.0, 0.0),
+        ))
+        .id();
+    app.update();
+    assert!(app.world().resource::<Events<Crushed>>().is_empty());
+
+    // A block sliding in from the right pins the player to the wall
+    app.world_mut().despawn(right_wall);
+    let pusher = app
+        .world_mut()
+        .spawn((
+            Platform,
+            Velocity(Vec2::new(-100.0, 0.0)),
+            BoxCollider::new(40.0, 100.0),
+            Transform::from_xyz(70.0, 50.0, 0.0),
+        ))
+        .id();
+
+    let (frame, crushed) = run_until_crushed(&mut app);
+    assert!((20..26).contains(&frame), "crushed on frame {frame}");
+    assert_eq!(
+        crushed,
+        vec![Crushed {
+            entity: player,
+            solids: [wall, pusher],
+        }]
+    );
+    let world = app.world();
+    assert_eq!(world.get::<Health>(player).unwrap().current, 70.0);
+    assert_eq!(
+        world.get::<Transform>(player).unwrap().translation,
+        Vec3::new(500.0, 25.0, 0.0)
+    );
+}
+
+#[test]
+fn test_pushable_crates_and_pressure_plates() {
+    let mut app = App::new();
+    app.init_resource::<Time>()
+        .init_resource::<SpatialHash>()
+        .add_event::<TriggerEntered>()
+        .add_event::<TriggerExited>()
+        .add_systems(
+            Update,
+            (
+                apply_gravity,
+                apply_velocity,
+                update_spatial_hash,
+                check_platform_collisions,
+                push_bodies,
+                update_triggers,
+                update_pressure_plates,
+            )
+                .chain(),
+        );
+
+    let layers = GameConfig::default().collision;
+    app.world_mut().spawn((
+        Platform,
+        BoxCollider::new(2000.0, 40.0),
+        layers.layers(CollisionLayer::Platform),
+        Transform::from_xyz(0.0, -20.0, 0.0),
+    ));
+    let spawn_crate = |app: &mut App, mass: f32, position: Vec2| {
+        app.world_mut()
+            .spawn((
+                Platform,
+                Pushable { mass },
+                Velocity::default(),
+                Gravity::default(),
+                DynamicBody,
+                Grounded::default(),
+                BoxCollider::new(40.0, 40.0),
+                layers.layers(CollisionLayer::Platform),
+                Transform::from_translation(position.extend(0.0)),
+            ))
+            .id()
+    };
+    let light = spawn_crate(&mut app, 1.0, Vec2::new(100.0, 100.0));
+    let heavy = spawn_crate(&mut app, 4.0, Vec2::new(-300.0, 20.0));
+    let plate = app
+        .world_mut()
+        .spawn((
+            PressurePlate::default(),
+            Trigger::default(),
+            BoxCollider::new(40.0, 10.0),
+            CollisionLayers::new(&[], &[CollisionLayer::Player, CollisionLayer::Platform]),
+            Transform::from_xyz(300.0, 5.0, 0.0),
+        ))
+        .id();
+    let player = app
+        .world_mut()
+        .spawn((
+            Player,
+            Velocity(Vec2::new(200.0, 0.0)),
+            Gravity::default(),
+            DynamicBody,
+            Grounded(true),
+            BoxCollider::new(40.0, 50.0),
+            layers.layers(CollisionLayer::Player),
+            Transform::from_xyz(-360.0, 25.0, 0.0),
+        ))
+        .id();
+
+    let step = |app: &mut App| {
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(1.0 / 60.0));
+        app.update();
+    };
+    let x = |app: &App, entity: Entity| app.world().get::<Transform>(entity).unwrap().translation.x;
+
+    // The light crate falls onto the floor, the heavy one crawls when pushed
+    for _ in 0..60 {
+        step(&mut app);
+    }
+    let world = app.world();
+    assert_eq!(world.get::<Transform>(light).unwrap().translation.y, 20.0);
+    assert!(world.get::<Grounded>(light).unwrap().0);
+    assert_eq!(world.get::<Velocity>(heavy).unwrap().0.x, 50.0);
+    // Held against the crate's face
+    assert!((x(&app, heavy) - x(&app, player) - 40.0).abs() < 1e-3);
+
+    // Pushed at full speed into the pressure plate, which it holds down
+    let start = x(&app, light) - 40.0;
+    app.world_mut()
+        .get_mut::<Transform>(player)
+        .unwrap()
+        .translation
+        .x = start;
+    step(&mut app);
+    assert_eq!(app.world().get::<Velocity>(light).unwrap().0.x, 200.0);
+    for _ in 0..120 {
+        if app.world().get::<PressurePlate>(plate).unwrap().pressed {
+            break;
+        }
+        step(&mut app);
+    }
+    assert!(app.world().get::<PressurePlate>(plate).unwrap().pressed);
+
+    // Once the player walks away the crate stops and stays on the plate
+    app.world_mut().get_mut::<Velocity>(player).unwrap().0.x = 0.0;
+    app.world_mut()
+        .get_mut::<Transform>(player)
+        .unwrap()
+        .translation
+        .x = 0.0;
+    step(&mut app);
+    step(&mut app);
+    let crate_x = x(&app, light);
+    step(&mut app);
+    assert_eq!(x(&app, light), crate_x);
+    assert!(app.world().get::<PressurePlate>(plate).unwrap().pressed);
+
+    // The crate can be stood on
+    app.world_mut()
+        .get_mut::<Transform>(player)
+        .unwrap()
+        .translation = Vec3::new(crate_x, 100.0, 0.0);
+    app.world_mut().get_mut::<Grounded>(player).unwrap().0 = false;
+    for _ in 0..60 {
+        step(&mut app);
+    }
+    let world = app.world();
+    assert_eq!(world.get::<Transform>(player).unwrap().translation.y, 65.0);
+    assert!(world.get::<Grounded>(player).unwrap().0);
+    assert_eq!(x(&app, light), crate_x);
+}
+
+#[test]
+fn test_any_body_with_gravity_lands_on_platforms() {
+    let mut app = App::new();
+    app.init_resource::<Time>()
+        .init_resource::<SpatialHash>()
+        .add_systems(
+            Update,
+            (
+                register_dynamic_bodies,
+                apply_gravity,
+                apply_velocity,
+                patrol_enemies,
+                update_spatial_hash,
+                check_platform_collisions,
+            )
+                .chain(),
+        );
+
+    let layers = GameConfig::default().collision;
+    app.world_mut().spawn((
+        Platform,
+        layers.layers(CollisionLayer::Platform),
+        BoxCollider::new(1000.0, 40.0),
+        Transform::from_xyz(0.0, -20.0, 0.0),
+    ));
+    // Neither spawns with `Grounded`, gravity alone makes them bodies
+    let enemy = app
+        .world_mut()
+        .spawn((
+            Enemy,
+            layers.layers(CollisionLayer::Enemy),
+            Patrol::new(0.0, 100.0, 50.0),
+            Velocity::default(),
+            Gravity::default(),
+            BoxCollider::new(40.0, 40.0),
+            Transform::from_xyz(0.0, 200.0, 0.0),
+        ))
+        .id();
+    let pickup = app
+        .world_mut()
+        .spawn((
+            Collectible::default(),
+            layers.layers(CollisionLayer::Pickup),
+            Velocity::default(),
+            Gravity::default(),
+            BoxCollider::new(20.0, 20.0),
+            Transform::from_xyz(200.0, 100.0, 0.0),
+        ))
+        .id();
+    // Kinematic bodies ignore gravity and platforms
+    let drone = app
+        .world_mut()
+        .spawn((
+            KinematicBody,
+            Velocity(Vec2::new(0.0, -100.0)),
+            Gravity::default(),
+            BoxCollider::new(20.0, 20.0),
+            Transform::from_xyz(-200.0, 50.0, 0.0),
+        ))
+        .id();
+
+    // Tracking `Grounded` without gravity does not make a dynamic body
+    let ghost = app
+        .world_mut()
+        .spawn((
+            Velocity(Vec2::new(0.0, -100.0)),
+            Grounded(false),
+            BoxCollider::new(20.0, 20.0),
+            Transform::from_xyz(400.0, 50.0, 0.0),
+        ))
+        .id();
+
+    for _ in 0..90 {
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(1.0 / 60.0));
+        app.update();
+    }
+
+    let world = app.world();
+    assert!(world.get::<DynamicBody>(ghost).is_none());
+    assert!(world.get::<Transform>(ghost).unwrap().translation.y < -50.0);
+    for (entity, height) in [(enemy, 20.0), (pickup, 10.0)] {
+        assert!(world.get::<DynamicBody>(entity).is_some());
+        assert!(world.get::<Grounded>(entity).unwrap().0);
+        assert_eq!(
+            world.get::<Transform>(entity).unwrap().translation.y,
+            height
+        );
+    }
+    // Still patrolling while it stands on the floor
+    assert_ne!(world.get::<Transform>(enemy).unwrap().translation.x, 0.0);
+
+    assert!(world.get::<DynamicBody>(drone).is_none());
+    assert_eq!(world.get::<Velocity>(drone).unwrap().0.y, -100.0);
+    assert!(world.get::<Transform>(drone).unwrap().translation.y < -50.0);
+}
+
+#[test]
+fn test_ramps_are_only_as_high_as_their_surface() {
+    let mut app = App::new();
+    app.init_resource::<Time>()
+        .init_resource::<SpatialHash>()
+        .add_event::<Crushed>()
+        .add_systems(
+            Update,
+            (update_spatial_hash, bounce_obstacles, detect_crushing).chain(),
+        );
+    // Rises from 0 on the left to 100 on the right, 200 wide
+    app.world_mut().spawn((
+        Platform,
+        Slope::new(0.0, 100.0),
+        BoxCollider::new(200.0, 100.0),
+        Transform::from_xyz(100.0, 50.0, 0.0),
+    ));
+
+    // Dropped over the low end, an obstacle bounces off the surface there
+    let obstacle = app
+        .world_mut()
+        .spawn((
+            Bounce::new(1000.0, 0.5),
+            BoxCollider::new(20.0, 20.0),
+            Transform::from_xyz(20.0, 150.0, 0.0),
+        ))
+        .id();
+    let mut lowest = f32::MAX;
+    for _ in 0..40 {
+        app.world_mut()
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_millis(20));
+        app.update();
+        lowest = lowest.min(
+            app.world()
+                .get::<Transform>(obstacle)
+                .unwrap()
+                .translation
+                .y,
+        );
+    }
+    assert!((lowest - 20.0).abs() < 1e-3, "lowest point {lowest}");
+
+    // Standing near the high end, under a ceiling 50 above the surface
+    let body = app
+        .world_mut()
+        .spawn((
+            Velocity::default(),
+            BoxCollider::new(40.0, 50.0),
+            Transform::from_xyz(180.0, 115.0, 0.0),
+        ))
+        .id();
+    let ceiling = app
+        .world_mut()
+        .spawn((
+            Platform,
+            Velocity(Vec2::new(0.0, -10.0)),
+            BoxCollider::new(400.0, 40.0),
+            Transform::from_xyz(180.0, 160.0, 0.0),
+        ))
+        .id();
+    app.update();
+    assert!(app.world().resource::<Events<Crushed>>().is_empty());
+
+    app.world_mut()
+        .get_mut::<Transform>(ceiling)
+        .unwrap()
+        .translation
+        .y = 151.0;
+    app.update();
+    let crushed: Vec<Crushed> = app
+        .world_mut()
+        .resource_mut::<Events<Crushed>>()
+        .drain()
+        .collect();
+    assert_eq!(crushed.len(), 1);
+    assert_eq!(crushed[0].entity, body);
+}
+
+#[test]
+fn test_crushed_enemies_and_obstacles_are_destroyed() {
+    let (mut app, _, player) = crush_app(GameConfig::default());
+    app.world_mut().despawn(player);
+    let enemy = app
+        .world_mut()
+        .spawn((
+            Enemy,
+            Patrol::new(0.0, 0.0, 0.0),
+            BoxCollider::new(40.0, 40.0),
+            Transform::from_xyz(-100.0, 20.0, 0.0),
+        ))
+        .id();
+    let obstacle = app
+        .world_mut()
+        .spawn((
+            Obstacle,
+            AutoMove::new(Vec2::NEG_X, 0.0),
+            BoxCollider::new(40.0, 40.0),
+            Transform::from_xyz(100.0, 20.0, 0.0),
+        ))
+        .id();
+    app.world_mut().spawn((
+        Platform,
+        Velocity(Vec2::new(0.0, -100.0)),
+        BoxCollider::new(400.0, 40.0),
+        Transform::from_xyz(0.0, 60.0, 0.0),
+    ));
+
+    let (_, crushed) = run_until_crushed(&mut app);
+    let mut crushed: Vec<Entity> = crushed.iter().map(|crushed| crushed.entity).collect();
+    let mut expected = vec![enemy, obstacle];
+    crushed.sort();
+    expected.sort();
+    assert_eq!(crushed, expected);
+
+    let world = app.world();
+    assert!(world.get_entity(enemy).is_none());
+    assert!(world.get::<Pooled>(obstacle).is_some());
+    assert!(world.get::<BoxCollider>(obstacle).is_none());
+}